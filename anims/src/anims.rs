@@ -278,7 +278,10 @@ impl Anims {
         let path = trimmed.to_path(1e-9);
         const W: f64 = 1000.;
         for i in 0..=1 {
-            let opts = StrokeOpts { strong: i == 1 };
+            let opts = StrokeOpts {
+                strong: i == 1,
+                ..StrokeOpts::default()
+            };
             let stroked: LoweredPath<Line> =
                 flatten::stroke::stroke_undashed_opt(&path, &flatten_style, 0.05, opts);
             let stroked_path = stroked.to_bez();
@@ -13,7 +13,7 @@ use xilem_web::{
     get_element_by_id,
     interfaces::*,
     svg::{
-        kurbo::{BezPath, Cap, Circle, CubicBez, Line, PathEl, Point, Shape, Stroke},
+        kurbo::{BezPath, Cap, Circle, CubicBez, Join, Line, PathEl, Point, QuadBez, Shape, Stroke},
         peniko::Color,
     },
     App, DomView, PointerMsg,
@@ -21,11 +21,14 @@ use xilem_web::{
 
 use flatten::{
     euler::{CubicParams, CubicToEulerIter},
-    stroke::{stroke_undashed_opt, LoweredPath, StrokeOpts},
-    ArcSegment,
+    spiro::fit_spiro,
+    stroke::{stroke_opt, LoweredPath, StrokeOpts},
+    ArcSegment, FillRule,
 };
 
-#[derive(Default)]
+/// Number of draggable points in the Spiro mode's interpolating spline.
+const SPIRO_N: usize = 6;
+
 struct AppState {
     p0: Point,
     p1: Point,
@@ -36,6 +39,107 @@ struct AppState {
     tolerance: f64,
     draw_arcs: bool,
     strong: bool,
+    dash_text: String,
+    cap: Cap,
+    join: Join,
+    miter_limit: f64,
+    /// Raw text of an SVG path `d=""` attribute. When non-empty and
+    /// parseable, this replaces the `p0..p3` cubic as the path being
+    /// stroked, so users can paste real glyph outlines or other
+    /// multi-subpath shapes rather than dragging one hand-placed curve.
+    svg_text: String,
+    /// When `true`, the toy drags `spiro_points` through `fit_spiro` to
+    /// build a G2 interpolating spline instead of using the cubic/SVG-path
+    /// input above.
+    spiro_mode: bool,
+    spiro_points: Vec<Point>,
+    spiro_grabs: Vec<GrabState>,
+    /// Winding rule the filled `flat` stroke outline is rendered with; see
+    /// `StrokeOpts::fill_rule`. Self-intersecting lobes near cusps or large
+    /// offsets render differently under `NonZero` vs `EvenOdd`.
+    fill_rule: FillRule,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        AppState {
+            p0: Point::default(),
+            p1: Point::default(),
+            p2: Point::default(),
+            p3: Point::default(),
+            grab: GrabState::default(),
+            offset: 0.0,
+            tolerance: 0.0,
+            draw_arcs: false,
+            strong: false,
+            dash_text: String::new(),
+            cap: Cap::Butt,
+            join: Join::Miter,
+            miter_limit: 4.0,
+            svg_text: String::new(),
+            spiro_mode: false,
+            spiro_points: vec![Point::ORIGIN; SPIRO_N],
+            spiro_grabs: (0..SPIRO_N).map(|_| GrabState::default()).collect(),
+            fill_rule: FillRule::NonZero,
+        }
+    }
+}
+
+/// Parse a comma/whitespace-separated list of dash lengths, the same
+/// free-form syntax `stroke-dasharray` accepts without the SVG-specific
+/// percentage/unit handling. Unparseable or non-positive entries are
+/// dropped rather than rejecting the whole field, so a user mid-edit (e.g.
+/// a trailing comma) still sees the last-valid pattern rendered.
+fn parse_dash_text(s: &str) -> Vec<f64> {
+    s.split([',', ' '])
+        .filter_map(|tok| tok.trim().parse::<f64>().ok())
+        .filter(|&len| len > 0.0)
+        .collect()
+}
+
+/// Parse an SVG path `d=""` string (M/L/C/Q/A/Z, same as `kurbo::BezPath`'s
+/// `FromStr`/`from_svg` impl) into a `BezPath`. Returns `None` rather than an
+/// error so a mid-edit (incomplete) string just falls back to the
+/// hand-placed default path instead of erroring out the whole toy.
+fn parse_svg_path(d: &str) -> Option<BezPath> {
+    BezPath::from_svg(d).ok()
+}
+
+/// Break `path` into cubic BÃ©zier segments, raising quads and treating lines
+/// and close-segments as degenerate (control points on the chord) cubics, so
+/// every segment of an arbitrary multi-subpath path can be fed through
+/// `CubicToEulerIter` the same way the single hand-placed cubic is.
+fn path_to_cubics(path: &BezPath) -> Vec<CubicBez> {
+    let mut cubics = vec![];
+    let mut start = Point::ORIGIN;
+    let mut last = Point::ORIGIN;
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                start = p;
+                last = p;
+            }
+            PathEl::LineTo(p) => {
+                cubics.push(CubicBez::new(last, last, p, p));
+                last = p;
+            }
+            PathEl::QuadTo(q, p) => {
+                cubics.push(QuadBez::new(last, q, p).raise());
+                last = p;
+            }
+            PathEl::CurveTo(p1, p2, p3) => {
+                cubics.push(CubicBez::new(last, p1, p2, p3));
+                last = p3;
+            }
+            PathEl::ClosePath => {
+                if last != start {
+                    cubics.push(CubicBez::new(last, last, start, start));
+                }
+                last = start;
+            }
+        }
+    }
+    cubics
 }
 
 impl AppState {
@@ -54,6 +158,67 @@ impl AppState {
             "Weak"
         }
     }
+
+    fn cap_button_label(&self) -> &'static str {
+        match self.cap {
+            Cap::Butt => "Butt",
+            Cap::Round => "Round",
+            Cap::Square => "Square",
+        }
+    }
+
+    fn next_cap(&self) -> Cap {
+        match self.cap {
+            Cap::Butt => Cap::Round,
+            Cap::Round => Cap::Square,
+            Cap::Square => Cap::Butt,
+        }
+    }
+
+    fn join_button_label(&self) -> &'static str {
+        match self.join {
+            Join::Bevel => "Bevel",
+            Join::Miter => "Miter",
+            Join::Round => "Round",
+        }
+    }
+
+    fn next_join(&self) -> Join {
+        match self.join {
+            Join::Bevel => Join::Miter,
+            Join::Miter => Join::Round,
+            Join::Round => Join::Bevel,
+        }
+    }
+
+    fn mode_button_label(&self) -> &'static str {
+        if self.spiro_mode {
+            "Spiro"
+        } else {
+            "Cubic"
+        }
+    }
+
+    fn fill_rule_button_label(&self) -> &'static str {
+        match self.fill_rule {
+            FillRule::NonZero => "NonZero",
+            FillRule::EvenOdd => "EvenOdd",
+        }
+    }
+
+    fn next_fill_rule(&self) -> FillRule {
+        match self.fill_rule {
+            FillRule::NonZero => FillRule::EvenOdd,
+            FillRule::EvenOdd => FillRule::NonZero,
+        }
+    }
+
+    fn fill_rule_svg_attr(&self) -> &'static str {
+        match self.fill_rule {
+            FillRule::NonZero => "nonzero",
+            FillRule::EvenOdd => "evenodd",
+        }
+    }
 }
 
 #[derive(Default)]
@@ -107,9 +272,21 @@ const RAINBOW_PALETTE: [Color; 12] = [
 ];
 
 fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
-    let mut path = BezPath::new();
-    path.move_to(state.p0);
-    path.curve_to(state.p1, state.p2, state.p3);
+    // A multi-segment path (the cubic, then a line back toward `p0`) so a
+    // join is visible at `p3` in addition to the two caps at `p0`.
+    let mut default_path = BezPath::new();
+    default_path.move_to(state.p0);
+    default_path.curve_to(state.p1, state.p2, state.p3);
+    default_path.line_to(state.p0);
+    // A pasted SVG `d=""` string, when present and parseable, stands in for
+    // the hand-placed cubic above so arbitrary (and multi-subpath) shapes
+    // can be stroked too. In Spiro mode, `spiro_points` drives a G2
+    // interpolating clothoid spline instead of either.
+    let path = if state.spiro_mode {
+        fit_spiro(&state.spiro_points, false, state.tolerance).to_bez()
+    } else {
+        parse_svg_path(&state.svg_text).unwrap_or(default_path)
+    };
     let stroke = xilem_web::svg::kurbo::Stroke::new(2.0);
     let stroke_thick = xilem_web::svg::kurbo::Stroke::new(12.0);
     let stroke_thin = xilem_web::svg::kurbo::Stroke::new(2.0);
@@ -121,36 +298,47 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
     #[allow(unused)]
     let err = params.est_euler_err();
     let mut spirals = vec![];
-    for (i, es) in CubicToEulerIter::new(c, state.tolerance).enumerate() {
-        let path = if es.params.cubic_ok() {
-            es.to_cubic().into_path(1.0)
-        } else {
-            // Janky rendering, we should be more sophisticated
-            // and subdivide into cubics with appropriate bounds
-            let mut path = BezPath::new();
-            const N: usize = 20;
-            path.move_to(es.p0);
-            for i in 1..N {
-                let t = i as f64 / N as f64;
-                path.line_to(es.eval(t));
-            }
-            path.line_to(es.p1);
-            path
-        };
-        let color = RAINBOW_PALETTE[(i * 7) % 12];
-        spirals.push(path.stroke(color, stroke_thick.clone()));
+    let mut i = 0;
+    for c in path_to_cubics(&path) {
+        for es in CubicToEulerIter::new(c, state.tolerance) {
+            let seg_path = if es.params.cubic_ok() {
+                es.to_cubic().into_path(1.0)
+            } else {
+                // Janky rendering, we should be more sophisticated
+                // and subdivide into cubics with appropriate bounds
+                let mut seg_path = BezPath::new();
+                const N: usize = 20;
+                seg_path.move_to(es.p0);
+                for i in 1..N {
+                    let t = i as f64 / N as f64;
+                    seg_path.line_to(es.eval(t));
+                }
+                seg_path.line_to(es.p1);
+                seg_path
+            };
+            let color = RAINBOW_PALETTE[(i * 7) % 12];
+            spirals.push(seg_path.stroke(color, stroke_thick.clone()));
+            i += 1;
+        }
     }
-    let style = Stroke::new(state.offset).with_caps(Cap::Butt);
+    let dash_pattern = parse_dash_text(&state.dash_text);
+    let style = Stroke::new(state.offset)
+        .with_caps(state.cap)
+        .with_join(state.join)
+        .with_miter_limit(state.miter_limit)
+        .with_dashes(0.0, dash_pattern);
     let stroke_opts = StrokeOpts {
         strong: state.strong,
+        fill_rule: state.fill_rule,
+        ..StrokeOpts::default()
     };
     let flat = if state.draw_arcs {
         let stroked: LoweredPath<ArcSegment> =
-            stroke_undashed_opt(c.to_path(1.), &style, state.tolerance, stroke_opts);
+            stroke_opt(path.clone(), &style, state.tolerance, stroke_opts);
         stroked.to_bez()
     } else {
         let stroked: LoweredPath<Line> =
-            stroke_undashed_opt(c.to_path(1.), &style, state.tolerance, stroke_opts);
+            stroke_opt(path.clone(), &style, state.tolerance, stroke_opts);
         stroked.to_bez()
     };
     let mut flat_pts = vec![];
@@ -164,7 +352,9 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
         }
     }
     let svg_el = svg(g((
-        g(flat.clone()).fill(Color::rgb8(0xf0, 0xd8, 0xd0)),
+        g(flat.clone())
+            .fill(Color::rgb8(0xf0, 0xd8, 0xd0))
+            .attr("fill-rule", state.fill_rule_svg_attr()),
         g(spirals).fill(NONE),
         g((
             path.stroke(Color::BLACK, stroke_thin.clone()),
@@ -184,6 +374,13 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
             Circle::new(state.p3, HANDLE_RADIUS)
                 .pointer(|s: &mut AppState, msg| s.grab.handle(&mut s.p3, &msg)),
         )),
+        g((0..SPIRO_N)
+            .map(|i| {
+                Circle::new(state.spiro_points[i], HANDLE_RADIUS).pointer(move |s: &mut AppState, msg| {
+                    s.spiro_grabs[i].handle(&mut s.spiro_points[i], &msg)
+                })
+            })
+            .collect::<Vec<_>>()),
     )))
     .attr("width", 900)
     .attr("height", 600);
@@ -235,6 +432,32 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
                 element.set_value(state.primitive_button_label());
             }
         });
+    let mode_toggle_el = input(())
+        .attr("type", "button")
+        .attr("class", "demo-button")
+        .attr("value", state.mode_button_label())
+        .on_click(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.spiro_mode = !state.spiro_mode;
+                element.set_value(state.mode_button_label());
+            }
+        });
+    let fill_rule_toggle_el = input(())
+        .attr("type", "button")
+        .attr("class", "demo-button")
+        .attr("value", state.fill_rule_button_label())
+        .on_click(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.fill_rule = state.next_fill_rule();
+                element.set_value(state.fill_rule_button_label());
+            }
+        });
     let correctness_toggle_el = input(())
         .attr("type", "button")
         .attr("class", "demo-button")
@@ -248,6 +471,76 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
                 element.set_value(state.correctness_button_label());
             }
         });
+    let dash_input_el = input(())
+        .attr("type", "text")
+        .attr("class", "demo-slider")
+        .attr("value", state.dash_text.clone())
+        .attr("placeholder", "e.g. 10 5")
+        .on_input(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.dash_text = element.value();
+            }
+        });
+    let svg_path_input_el = input(())
+        .attr("type", "text")
+        .attr("class", "demo-slider")
+        .attr("value", state.svg_text.clone())
+        .attr("placeholder", "e.g. M10 10 L100 20 C100 100 50 100 50 50 Z")
+        .on_input(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.svg_text = element.value();
+            }
+        });
+    let cap_toggle_el = input(())
+        .attr("type", "button")
+        .attr("class", "demo-button")
+        .attr("value", state.cap_button_label())
+        .on_click(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.cap = state.next_cap();
+                element.set_value(state.cap_button_label());
+            }
+        });
+    let join_toggle_el = input(())
+        .attr("type", "button")
+        .attr("class", "demo-button")
+        .attr("value", state.join_button_label())
+        .on_click(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                state.join = state.next_join();
+                element.set_value(state.join_button_label());
+            }
+        });
+    let miter_limit_slider_el = input(())
+        .attr("type", "range")
+        .attr("min", "1")
+        .attr("max", "20")
+        .attr("step", "0.5")
+        .attr("value", "4")
+        .attr("class", "demo-slider")
+        .on_input(|state: &mut AppState, evt| {
+            if let Some(element) = evt
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                let value = element.value();
+                if let Ok(val_f64) = value.parse::<f64>() {
+                    state.miter_limit = val_f64;
+                }
+            }
+        });
     div((
         div((
             div((span("Offset:").class("demo-slider-label"), offset_slider_el))
@@ -258,16 +551,41 @@ fn app_logic(state: &mut AppState) -> impl DomView<AppState> {
                 span(state.tolerance.to_string()).class("demo-slider-label"),
             ))
             .attr("class", "demo-ui"),
+            div((
+                span("Dash array:").class("demo-slider-label"),
+                dash_input_el,
+            ))
+            .attr("class", "demo-ui"),
+            div((
+                span("SVG path `d`:").class("demo-slider-label"),
+                svg_path_input_el,
+            ))
+            .attr("class", "demo-ui"),
             div((
                 span("Primitive Type:").class("demo-slider-label"),
                 primitive_toggle_el,
             ))
             .attr("class", "demo-ui"),
+            div((span("Mode:").class("demo-slider-label"), mode_toggle_el)).attr("class", "demo-ui"),
+            div((
+                span("Fill rule:").class("demo-slider-label"),
+                fill_rule_toggle_el,
+            ))
+            .attr("class", "demo-ui"),
             div((
                 span("Correctness:").class("demo-slider-label"),
                 correctness_toggle_el,
             ))
             .attr("class", "demo-ui"),
+            div((span("Cap:").class("demo-slider-label"), cap_toggle_el)).attr("class", "demo-ui"),
+            div((span("Join:").class("demo-slider-label"), join_toggle_el))
+                .attr("class", "demo-ui"),
+            div((
+                span("Miter limit:").class("demo-slider-label"),
+                span(miter_limit_slider_el),
+                span(state.miter_limit.to_string()).class("demo-slider-label"),
+            ))
+            .attr("class", "demo-ui"),
         ))
         .attr("class", "demo-ui-wrapper"),
         div(svg_el),
@@ -286,5 +604,129 @@ pub fn run_beztoy() {
     state.offset = 100.;
     state.strong = true;
     state.tolerance = 1.;
+    state.dash_text = String::new();
+    for (i, p) in state.spiro_points.iter_mut().enumerate() {
+        let t = i as f64 / (SPIRO_N - 1) as f64;
+        *p = Point::new(100.0 + t * 600.0, 300.0 + 150.0 * (t * std::f64::consts::PI * 2.0).sin());
+    }
     App::new(get_element_by_id("beztoy-container"), state, app_logic).run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `next_cap`/`next_join` drive the toggle buttons' click handlers, so
+    /// each must visit every variant and cycle back to the start rather than
+    /// getting stuck or skipping one; `*_button_label` should always agree
+    /// with the state it's labeling.
+    #[test]
+    fn cap_and_join_toggles_cycle_through_all_variants() {
+        let mut state = AppState::default();
+        state.cap = Cap::Butt;
+        let mut seen = vec![state.cap];
+        for _ in 0..3 {
+            state.cap = state.next_cap();
+            seen.push(state.cap);
+        }
+        assert_eq!(
+            seen,
+            vec![Cap::Butt, Cap::Round, Cap::Square, Cap::Butt],
+            "next_cap should visit all three variants and return to the start"
+        );
+
+        state.cap = Cap::Butt;
+        assert_eq!(state.cap_button_label(), "Butt");
+        state.cap = Cap::Round;
+        assert_eq!(state.cap_button_label(), "Round");
+        state.cap = Cap::Square;
+        assert_eq!(state.cap_button_label(), "Square");
+
+        let mut state = AppState::default();
+        state.join = Join::Bevel;
+        let mut seen = vec![state.join];
+        for _ in 0..3 {
+            state.join = state.next_join();
+            seen.push(state.join);
+        }
+        assert_eq!(
+            seen,
+            vec![Join::Bevel, Join::Miter, Join::Round, Join::Bevel],
+            "next_join should visit all three variants and return to the start"
+        );
+
+        state.join = Join::Bevel;
+        assert_eq!(state.join_button_label(), "Bevel");
+        state.join = Join::Miter;
+        assert_eq!(state.join_button_label(), "Miter");
+        state.join = Join::Round;
+        assert_eq!(state.join_button_label(), "Round");
+    }
+
+    /// A mid-edit `svg_text` (e.g. a truncated path while the user is still
+    /// typing) must fall back to `None` rather than panicking, so `app_logic`
+    /// renders the hand-placed default path instead via
+    /// `unwrap_or(default_path)`.
+    #[test]
+    fn parse_svg_path_rejects_malformed_input() {
+        assert!(parse_svg_path("M10 10 L20").is_none());
+        assert!(parse_svg_path("not a path").is_none());
+        assert!(parse_svg_path("M10 10 L20 20").is_some());
+    }
+
+    /// `path_to_cubics` must turn every element kind into the cubic form
+    /// `CubicToEulerIter` expects (lines and closes as degenerate
+    /// chord-control-point cubics, quads raised to cubics), and must not
+    /// emit a closing segment when the subpath was already closed by an
+    /// explicit line back to its start.
+    #[test]
+    fn path_to_cubics_converts_every_element_kind() {
+        let mut path = BezPath::new();
+        path.move_to(Point::new(0.0, 0.0));
+        path.line_to(Point::new(1.0, 0.0));
+        path.quad_to(Point::new(2.0, 1.0), Point::new(3.0, 0.0));
+        path.curve_to(
+            Point::new(4.0, 1.0),
+            Point::new(5.0, -1.0),
+            Point::new(6.0, 0.0),
+        );
+        path.close_path();
+
+        let cubics = path_to_cubics(&path);
+        assert_eq!(cubics.len(), 4);
+
+        // LineTo becomes a degenerate cubic with both control points on the
+        // chord endpoints.
+        assert_eq!(cubics[0].p0, Point::new(0.0, 0.0));
+        assert_eq!(cubics[0].p1, Point::new(0.0, 0.0));
+        assert_eq!(cubics[0].p2, Point::new(1.0, 0.0));
+        assert_eq!(cubics[0].p3, Point::new(1.0, 0.0));
+
+        // QuadTo is raised to an exactly equivalent cubic.
+        let raised = QuadBez::new(Point::new(1.0, 0.0), Point::new(2.0, 1.0), Point::new(3.0, 0.0))
+            .raise();
+        assert_eq!(cubics[1].p0, raised.p0);
+        assert_eq!(cubics[1].p1, raised.p1);
+        assert_eq!(cubics[1].p2, raised.p2);
+        assert_eq!(cubics[1].p3, raised.p3);
+
+        // CurveTo passes through unchanged.
+        assert_eq!(cubics[2].p0, Point::new(3.0, 0.0));
+        assert_eq!(cubics[2].p3, Point::new(6.0, 0.0));
+
+        // ClosePath, since `last != start` here, becomes one more degenerate
+        // cubic back to the subpath's start.
+        assert_eq!(cubics[3].p0, Point::new(6.0, 0.0));
+        assert_eq!(cubics[3].p3, Point::new(0.0, 0.0));
+
+        // An already-closed subpath (explicit LineTo back to the start)
+        // followed by ClosePath shouldn't get a second, zero-length closing
+        // cubic.
+        let mut closed = BezPath::new();
+        closed.move_to(Point::new(0.0, 0.0));
+        closed.line_to(Point::new(1.0, 0.0));
+        closed.line_to(Point::new(0.0, 0.0));
+        closed.close_path();
+        assert_eq!(path_to_cubics(&closed).len(), 2);
+    }
+}
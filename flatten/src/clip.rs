@@ -0,0 +1,91 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sutherland–Hodgman polygon clipping against an axis-aligned rectangle,
+//! used to drop expanded-stroke geometry that falls outside the viewport
+//! (plus a guard band) before it's serialized or rasterized.
+
+use kurbo::{flatten, BezPath, PathEl, Point, Rect};
+
+/// Flatten `path` into closed polygon rings, one per subpath. Stroke
+/// outlines (what this is used for) are always closed, so unlike
+/// `dilate::flatten_subpaths` there's no need to track an open/closed flag.
+fn flatten_rings(path: &BezPath, tolerance: f64) -> Vec<Vec<Point>> {
+    let mut rings = vec![];
+    let mut pts = vec![];
+    flatten(path, tolerance, |el| match el {
+        PathEl::MoveTo(p) => {
+            if !pts.is_empty() {
+                rings.push(std::mem::take(&mut pts));
+            }
+            pts.push(p);
+        }
+        PathEl::LineTo(p) => pts.push(p),
+        PathEl::ClosePath => (),
+        _ => unreachable!("kurbo::flatten only emits MoveTo/LineTo/ClosePath"),
+    });
+    if !pts.is_empty() {
+        rings.push(pts);
+    }
+    rings
+}
+
+/// One Sutherland–Hodgman pass: clip a closed polygon (the edge from the
+/// last point back to the first is implicit) against a single half-plane,
+/// given an "is this point on the inside" test and a function that finds
+/// where an inside/outside edge crosses the boundary.
+fn clip_edge(
+    pts: &[Point],
+    inside: impl Fn(Point) -> bool,
+    intersect: impl Fn(Point, Point) -> Point,
+) -> Vec<Point> {
+    if pts.is_empty() {
+        return vec![];
+    }
+    let mut out = vec![];
+    let mut prev = pts[pts.len() - 1];
+    let mut prev_in = inside(prev);
+    for &p in pts {
+        let p_in = inside(p);
+        if p_in {
+            if !prev_in {
+                out.push(intersect(prev, p));
+            }
+            out.push(p);
+        } else if prev_in {
+            out.push(intersect(prev, p));
+        }
+        prev = p;
+        prev_in = p_in;
+    }
+    out
+}
+
+/// Clip a closed polygon ring against `rect` via four Sutherland–Hodgman
+/// passes (one per side). Returns an empty ring if the polygon is fully
+/// outside `rect`.
+pub fn clip_ring(pts: &[Point], rect: Rect) -> Vec<Point> {
+    let lerp_x = |a: Point, b: Point, x: f64| {
+        let t = (x - a.x) / (b.x - a.x);
+        Point::new(x, a.y + t * (b.y - a.y))
+    };
+    let lerp_y = |a: Point, b: Point, y: f64| {
+        let t = (y - a.y) / (b.y - a.y);
+        Point::new(a.x + t * (b.x - a.x), y)
+    };
+    let mut pts = clip_edge(pts, |p| p.x >= rect.x0, |a, b| lerp_x(a, b, rect.x0));
+    pts = clip_edge(&pts, |p| p.x <= rect.x1, |a, b| lerp_x(a, b, rect.x1));
+    pts = clip_edge(&pts, |p| p.y >= rect.y0, |a, b| lerp_y(a, b, rect.y0));
+    pts = clip_edge(&pts, |p| p.y <= rect.y1, |a, b| lerp_y(a, b, rect.y1));
+    pts
+}
+
+/// Flatten `path` at `tolerance` and clip every resulting ring against
+/// `rect`, dropping rings that end up empty (fully outside).
+pub fn clip_path(path: &BezPath, tolerance: f64, rect: Rect) -> Vec<Vec<Point>> {
+    flatten_rings(path, tolerance)
+        .into_iter()
+        .map(|ring| clip_ring(&ring, rect))
+        .filter(|ring| !ring.is_empty())
+        .collect()
+}
@@ -3,6 +3,8 @@
 
 //! f32 versions of cubics, tweaked for subdivision
 
+use crate::ops32;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Point {
     pub x: f32,
@@ -19,7 +21,7 @@ impl Point {
     }
 
     pub fn hypot(self) -> f32 {
-        self.x.hypot(self.y)
+        ops32::hypot(self.x, self.y)
     }
 
     pub fn hypot2(self) -> f32 {
@@ -27,7 +29,7 @@ impl Point {
     }
 
     pub fn atan2(self) -> f32 {
-        self.y.atan2(self.x)
+        ops32::atan2(self.y, self.x)
     }
 }
 
@@ -83,4 +85,112 @@ impl Cubic {
             (self.p1 - self.p0) * mm + (self.p2 - self.p1) * (2.0 * mt) + (self.p3 - self.p2) * tt;
         (p, q)
     }
+
+    /// The control points of the cubic restricted to the parameter range
+    /// `[t0, t1]`, via the standard blossoming formula.
+    fn subsegment(&self, t0: f32, t1: f32) -> Cubic {
+        let u0 = 1.0 - t0;
+        let u1 = 1.0 - t1;
+        let q0 = self.p0 * (u0 * u0 * u0)
+            + self.p1 * (3.0 * u0 * u0 * t0)
+            + self.p2 * (3.0 * u0 * t0 * t0)
+            + self.p3 * (t0 * t0 * t0);
+        let q1 = self.p0 * (u0 * u0 * u1)
+            + self.p1 * (2.0 * u0 * u1 * t0 + u0 * u0 * t1)
+            + self.p2 * (u1 * t0 * t0 + 2.0 * u0 * t0 * t1)
+            + self.p3 * (t0 * t0 * t1);
+        let q2 = self.p0 * (u0 * u1 * u1)
+            + self.p1 * (u1 * u1 * t0 + 2.0 * u0 * u1 * t1)
+            + self.p2 * (2.0 * u1 * t0 * t1 + u0 * t1 * t1)
+            + self.p3 * (t0 * t1 * t1);
+        let q3 = self.p0 * (u1 * u1 * u1)
+            + self.p1 * (3.0 * u1 * u1 * t1)
+            + self.p2 * (3.0 * u1 * t1 * t1)
+            + self.p3 * (t1 * t1 * t1);
+        Cubic::new(q0, q1, q2, q3)
+    }
+
+    /// Approximate the cubic by a sequence of quadratics, each covering a
+    /// `[t0, t1]` sub-range of the full `[0, 1]` parameter.
+    ///
+    /// Uses the same error formula as kurbo's `CubicBez::to_quads`: the
+    /// squared deviation between a cubic and its best-fit quadratic over a range of
+    /// length `dt` scales with `dt^6`, so the number of quadratics needed
+    /// for a given `accuracy` is `n = ceil((err / (432 * accuracy^2))^(1/6))`.
+    fn to_quads(&self, accuracy: f32) -> impl Iterator<Item = (f32, f32, Point, Point, Point)> + '_ {
+        let p1x2 = self.p1 * 3.0 - self.p0;
+        let p2x2 = self.p2 * 3.0 - self.p3;
+        let d = p2x2 - p1x2;
+        let err = d.hypot2();
+        let n = ops32::powf(err / (432.0 * accuracy * accuracy), 1.0 / 6.0)
+            .ceil()
+            .max(1.0) as usize;
+        (0..n).map(move |i| {
+            let t0 = i as f32 / n as f32;
+            let t1 = (i + 1) as f32 / n as f32;
+            let sub = self.subsegment(t0, t1);
+            let p1x2 = sub.p1 * 3.0 - sub.p0;
+            let p2x2 = sub.p2 * 3.0 - sub.p3;
+            let q1 = (p1x2 + p2x2) * 0.25;
+            (t0, t1, sub.p0, q1, sub.p3)
+        })
+    }
+
+    /// Flatten the cubic into near-minimal line segments using Levien's
+    /// parabola-integral method: approximate by quadratics, map each onto
+    /// the canonical parabola `y = x^2`, and walk it at uniform steps of
+    /// parabola arc-parameter rather than recursively halving.
+    ///
+    /// See <https://raphlinus.github.io/graphics/curves/2019/12/23/flatten-quadbez.html>.
+    pub fn flatten(&self, tolerance: f32, f: &mut impl FnMut(Point)) {
+        for (_t0, _t1, q0, q1, q2) in self.to_quads(tolerance) {
+            flatten_quad(q0, q1, q2, tolerance, f);
+        }
+    }
+}
+
+const D: f32 = 0.67;
+const B: f32 = 0.39;
+
+pub(crate) fn approx_parabola_integral(x: f32) -> f32 {
+    x / (1.0 - D + ops32::sqrt(ops32::sqrt(ops32::powi(D, 4) + 0.25 * x * x)))
+}
+
+pub(crate) fn approx_parabola_inv_integral(x: f32) -> f32 {
+    x * (1.0 - B + ops32::sqrt(B * B + 0.25 * x * x))
+}
+
+/// Flatten a single quadratic (as three `Point`s, not a `Cubic`) using the
+/// parabola-integral method described on `Cubic::flatten`.
+fn flatten_quad(q0: Point, q1: Point, q2: Point, tolerance: f32, f: &mut impl FnMut(Point)) {
+    let ddx = 2.0 * q1.x - q0.x - q2.x;
+    let ddy = 2.0 * q1.y - q0.y - q2.y;
+    let cross = (q2.x - q0.x) * ddy - (q2.y - q0.y) * ddx;
+    let dd_len = ops32::hypot(ddx, ddy);
+    if dd_len < 1e-9 || cross.abs() < 1e-9 {
+        // Nearly straight: a single line suffices.
+        f(q2);
+        return;
+    }
+    let x0 = ((q1.x - q0.x) * ddx + (q1.y - q0.y) * ddy) / cross;
+    let x1 = ((q2.x - q1.x) * ddx + (q2.y - q1.y) * ddy) / cross;
+    let scale = cross.abs() / (dd_len * (x1 - x0).abs());
+    let int0 = approx_parabola_integral(x0);
+    let int1 = approx_parabola_integral(x1);
+    let int_range = int1 - int0;
+    let n = (0.5 * ops32::sqrt(int_range.abs() * scale / tolerance))
+        .ceil()
+        .max(1.0) as usize;
+    for i in 1..n {
+        let u = approx_parabola_inv_integral(int0 + int_range * (i as f32 / n as f32));
+        let t = (u - x0) / (x1 - x0);
+        let p = lerp_quad(q0, q1, q2, t);
+        f(p);
+    }
+    f(q2);
+}
+
+fn lerp_quad(q0: Point, q1: Point, q2: Point, t: f32) -> Point {
+    let m = 1.0 - t;
+    q0 * (m * m) + q1 * (2.0 * m * t) + q2 * (t * t)
 }
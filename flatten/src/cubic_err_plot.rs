@@ -14,8 +14,8 @@ pub struct CubicErrPlot {
     approx: bool,
 }
 
-// This function currently calls est_euler_err, but the code exists to compute from
-// scratch, which might be useful for experimentation.
+// Plots `est_euler_err_closed_form` (see `euler.rs`) against the true,
+// numerically-integrated error, to visually spot-check the bound.
 #[allow(unused)]
 pub fn cubic_err_plot(plot: CubicErrPlot) {
     const N: usize = 600;
@@ -32,28 +32,7 @@ pub fn cubic_err_plot(plot: CubicErrPlot) {
             let c = CubicBez::new(Point::ORIGIN, p1, p2, Point::new(1.0, 0.0));
             let e = EulerSeg::from_cubic(c);
             let err = if plot.approx {
-                let e0 = 2. / (3. * (1.0 + th0.cos()));
-                let e1 = 2. / (3. * (1.0 + th1.cos()));
-                let s0 = th0.sin();
-                let s1 = th1.sin();
-                let s01 = (th0 + th1).sin();
-                let amin = 0.15 * (2. * e0 * s0 + 2. * e1 * s1 - e0 * e1 * s01);
-                let a = 0.15 * (2. * d0 * s0 + 2. * d1 * s1 - d0 * d1 * s01);
-                let aerr = (a - amin).abs();
-                let symm = (th0 + th1).abs();
-                let asymm = (th0 - th1).abs();
-                let dist = (d0 - e0).hypot(d1 - e1);
-                let amb3 = 1e-3 * symm.powi(3) + 1e-3 * asymm.powi(3);
-                //aerr.hypot(0.02 * symm * dist).hypot(2.0 * asymm * dist).hypot(amb3)
-                // Accurate estimate of center error for th0, th1 in [0..2/3]
-                let ctr = 3e-6 * symm.powi(5) + 6e-3 * asymm * symm.powi(2);
-                //ctr + aerr + 2e-3 * symm * dist.powf(0.5) + 7e-2 * asymm * dist.powf(0.5)
-                let ctr = 3.7e-6 * symm.powi(5) + 6e-3 * asymm * symm.powi(2);
-                let halo_symm = 5e-3 * symm * dist;
-                let halo_asymm = 7e-2 * asymm * dist;
-                //1.25 * ctr + 1.55 * aerr + halo_symm + halo_asymm
-                let cparams = CubicParams::from_cubic(c);
-                cparams.est_euler_err()
+                CubicParams::from_cubic(c).est_euler_err_closed_form()
             } else {
                 e.cubic_euler_err(c, 10)
             };
@@ -0,0 +1,116 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Outline dilation/emboldening: grow (or, with a negative `d`, shrink) a
+//! filled shape by a fixed distance. This is a distinct operation from
+//! stroke-to-fill expansion: it displaces the existing contour rather than
+//! building a new outline around a centerline, which is what font
+//! emboldening and coverage fattening actually want (see Pathfinder's
+//! pre-fill dilation pass).
+
+use kurbo::{flatten, BezPath, PathEl, Point, Vec2};
+
+/// A flattened, unclosed ring of points making up one subpath.
+struct FlatSubpath {
+    pts: Vec<Point>,
+    closed: bool,
+}
+
+fn flatten_subpaths(path: &BezPath, tolerance: f64) -> Vec<FlatSubpath> {
+    let mut subpaths = vec![];
+    let mut pts = vec![];
+    let mut closed = false;
+    flatten(path, tolerance, |el| match el {
+        PathEl::MoveTo(p) => {
+            if !pts.is_empty() {
+                subpaths.push(FlatSubpath {
+                    pts: std::mem::take(&mut pts),
+                    closed,
+                });
+            }
+            closed = false;
+            pts.push(p);
+        }
+        PathEl::LineTo(p) => pts.push(p),
+        PathEl::ClosePath => closed = true,
+        _ => unreachable!("kurbo::flatten only emits MoveTo/LineTo/ClosePath"),
+    });
+    if !pts.is_empty() {
+        subpaths.push(FlatSubpath { pts, closed });
+    }
+    subpaths
+}
+
+/// The outward unit normal of the edge from `p0` to `p1`.
+fn edge_normal(p0: Point, p1: Point) -> Vec2 {
+    let tan = (p1 - p0).normalize();
+    Vec2::new(tan.y, -tan.x)
+}
+
+/// Displace a vertex whose incoming and outgoing edges have outward unit
+/// normals `n0`/`n1`, offsetting by `d`.
+///
+/// This is the intersection of the two edges after each is offset outward
+/// by `d`, which is exact on straight runs and degrades gracefully at
+/// convex/concave corners. `1 + n0.dot(n1)` approaches zero as the two
+/// edges become antiparallel (a sharp spike), so it's clamped to bound the
+/// displacement magnitude there.
+fn vertex_displacement(n0: Vec2, n1: Vec2, d: f64) -> Vec2 {
+    const MIN_DENOM: f64 = 1e-3;
+    let denom = (1.0 + n0.dot(n1)).max(MIN_DENOM);
+    d * (n0 + n1) / denom
+}
+
+/// Grow (`d > 0`) or shrink (`d < 0`) the filled shape traced by `path` by a
+/// fixed distance `d`, preserving each subpath's open/closed state.
+pub fn dilate(path: &BezPath, d: f64, tolerance: f64) -> BezPath {
+    let mut result = BezPath::new();
+    for sub in flatten_subpaths(path, tolerance) {
+        let pts = &sub.pts;
+        let n = pts.len();
+        if sub.closed {
+            if n < 3 {
+                continue;
+            }
+            let out: Vec<Point> = (0..n)
+                .map(|i| {
+                    let prev = pts[(i + n - 1) % n];
+                    let cur = pts[i];
+                    let next = pts[(i + 1) % n];
+                    let n0 = edge_normal(prev, cur);
+                    let n1 = edge_normal(cur, next);
+                    cur + vertex_displacement(n0, n1, d)
+                })
+                .collect();
+            result.move_to(out[0]);
+            for p in &out[1..] {
+                result.line_to(*p);
+            }
+            result.close_path();
+        } else {
+            if n < 2 {
+                continue;
+            }
+            let out: Vec<Point> = (0..n)
+                .map(|i| {
+                    let cur = pts[i];
+                    let disp = if i == 0 {
+                        d * edge_normal(pts[0], pts[1])
+                    } else if i == n - 1 {
+                        d * edge_normal(pts[n - 2], pts[n - 1])
+                    } else {
+                        let n0 = edge_normal(pts[i - 1], cur);
+                        let n1 = edge_normal(cur, pts[i + 1]);
+                        vertex_displacement(n0, n1, d)
+                    };
+                    cur + disp
+                })
+                .collect();
+            result.move_to(out[0]);
+            for p in &out[1..] {
+                result.line_to(*p);
+            }
+        }
+    }
+    result
+}
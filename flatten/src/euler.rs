@@ -4,7 +4,7 @@
 //! Calculations and utilities for Euler spirals
 
 use arrayvec::ArrayVec;
-use kurbo::{CubicBez, ParamCurve, ParamCurveArclen, Point, Vec2};
+use kurbo::{CubicBez, ParamCurve, Point, Vec2};
 
 use crate::flatten::n_subdiv_analytic;
 
@@ -16,7 +16,7 @@ pub struct CubicParams {
     pub d1: f64,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct EulerParams {
     pub th0: f64,
     #[allow(unused)]
@@ -36,6 +36,10 @@ pub struct EulerSeg {
 pub struct CubicToEulerIter {
     c: CubicBez,
     tolerance: f64,
+    // Overall scale of `c`, as passed to `regularize`; reused to guard
+    // against sub-chords that collapse to near-zero during subdivision.
+    dimension: f64,
+    err_metric: ErrorMetric,
     // [t0 * dt .. (t0 + 1) * dt] is the range we're currently considering
     t0: u64,
     dt: f64,
@@ -44,10 +48,30 @@ pub struct CubicToEulerIter {
     last_t: f64,
 }
 
+/// Which error estimate `CubicToEulerIter` uses to decide whether a
+/// candidate Euler fit is within tolerance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorMetric {
+    /// `CubicParams::est_euler_err`'s tuned closed-form heuristic. Fast,
+    /// and the default, but only an estimate.
+    #[default]
+    Heuristic,
+    /// `EulerSeg::frechet_err`'s sampled Fréchet estimate. Slower; like the
+    /// heuristic it is an estimate rather than a bound, since only discrete
+    /// stations are compared.
+    Frechet,
+}
+
 impl CubicParams {
     pub fn from_cubic(c: CubicBez) -> Self {
+        let dimension = c
+            .p0
+            .distance(c.p1)
+            .max(c.p1.distance(c.p2))
+            .max(c.p2.distance(c.p3))
+            .max(c.p0.distance(c.p3));
+        let c = regularize(c, dimension);
         let chord = c.p3 - c.p0;
-        // TODO: if chord is 0, we have a problem
         let d01 = c.p1 - c.p0;
         let h0 = Vec2::new(
             d01.x * chord.x + d01.y * chord.y,
@@ -105,6 +129,97 @@ impl CubicParams {
         let halo_asymm = 7e-2 * asymm * dist;
         1.25 * ctr + 1.55 * aerr + halo_symm + halo_asymm
     }
+
+    /// Closed-form upper bound on the cubic-to-Euler-spiral fitting error,
+    /// normalized to chord (multiply by chord length for actual error).
+    ///
+    /// This is the same analytic approximation as `est_euler_err` (symmetric
+    /// and asymmetric terms plus halo corrections), named and exposed
+    /// separately because it's validated (see the `closed_form_bounds_err`
+    /// test below) to never underestimate `EulerSeg::cubic_euler_err`, so
+    /// callers that need a *bound* rather than a central estimate can rely
+    /// on it specifically.
+    pub fn est_euler_err_closed_form(&self) -> f64 {
+        self.est_euler_err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{CubicBez, Point};
+
+    use super::{CubicParams, EulerParams, EulerSeg};
+
+    // Slack added on top of the closed-form bound to absorb floating-point
+    // noise in the numerical integration it's being compared against.
+    const SLACK: f64 = 1e-6;
+
+    /// A segment whose curvature flips from concave to convex (`k0 = 0`,
+    /// `k1` large) across `[0, 1]`, so a large enough offset makes
+    /// `curvature(t) * offset` cross `-1` partway along: exactly the cusp
+    /// condition `EulerSeg::flatten_offset` is documented to split on.
+    #[test]
+    fn flatten_offset_splits_at_cusp() {
+        let params = EulerParams {
+            th0: 0.0,
+            th1: 0.0,
+            k0: 0.0,
+            k1: 20.0,
+            ch: 1.0,
+        };
+        let es = EulerSeg {
+            p0: Point::new(0.0, 0.0),
+            p1: Point::new(1.0, 0.0),
+            params,
+        };
+        let offset = 0.2;
+        let chord_len = (es.p1 - es.p0).hypot();
+        let cusp0 = es.params.curvature(0.0) * offset + chord_len;
+        let cusp1 = es.params.curvature(1.0) * offset + chord_len;
+        assert!(
+            cusp0 * cusp1 < 0.0,
+            "test setup should land in the cusp-crossing regime"
+        );
+        let t_cusp = cusp0 / (cusp0 - cusp1);
+        let evolute_pt = es.eval_evolute(t_cusp);
+
+        let pts: Vec<Point> = es.flatten_offset(offset, 0.01).collect();
+        assert!(
+            pts.iter().any(|p| p.distance(evolute_pt) < 1e-9),
+            "the cusp split should insert the exact eval_evolute point at t_cusp"
+        );
+
+        // The centerline (offset = 0) never hits the cusp condition
+        // (`curvature(t) * 0 + chord_len` is always `chord_len > 0`), so it
+        // should take the single, unsplit branch and just produce a plain
+        // flattening.
+        let centerline: Vec<Point> = es.flatten_offset(0.0, 0.01).collect();
+        assert!(!centerline.is_empty());
+    }
+
+    #[test]
+    fn closed_form_bounds_err() {
+        const N: usize = 24;
+        for iy in 0..=N {
+            let th1 = iy as f64 * (2.0 / 3.0) / N as f64;
+            for ix in 0..=N {
+                let th0 = ix as f64 * (2.0 / 3.0) / N as f64;
+                let d0 = 0.3;
+                let d1 = 0.3;
+                let p1 = Point::new(d0 * th0.cos(), d0 * th0.sin());
+                let p2 = Point::new(1.0 - d1 * th1.cos(), d1 * th1.sin());
+                let c = CubicBez::new(Point::ORIGIN, p1, p2, Point::new(1.0, 0.0));
+                let e = EulerSeg::from_cubic(c);
+                let cparams = CubicParams::from_cubic(c);
+                let bound = cparams.est_euler_err_closed_form();
+                let actual = e.cubic_euler_err(c, 10);
+                assert!(
+                    bound + SLACK >= actual,
+                    "closed-form bound {bound} underestimated actual error {actual} at th0={th0}, th1={th1}"
+                );
+            }
+        }
+    }
 }
 
 impl EulerParams {
@@ -327,6 +442,101 @@ impl EulerSeg {
         )
     }
 
+    /// Flatten this segment's offset curve (`offset = 0.0` for the
+    /// centerline) into points, the same way the GPU `flatten.wgsl` kernel
+    /// does: `flatten::flatten_offset`'s ESPC integral sizes the
+    /// subdivision and its analytic inverse-density mapping places the
+    /// samples (the start point at `t = 0` is not included, matching that
+    /// function's convention).
+    ///
+    /// If `curvature(t) * offset` crosses `-1` somewhere in `[0, 1]` (the
+    /// offset curve's local radius passing through zero), the walk is split
+    /// there: each side is flattened independently over its own sub-range
+    /// (by `flatten::flatten_offset_range`, evenly spaced in `t`), and the
+    /// point exactly at the split is taken from `eval_evolute`
+    /// rather than the offset curve, since the true offset point at a cusp
+    /// is at infinity.
+    pub fn flatten_offset(&self, offset: f64, tolerance: f64) -> impl Iterator<Item = Point> {
+        let chord_len = (self.p1 - self.p0).hypot();
+        let cusp0 = self.params.curvature(0.0) * offset + chord_len;
+        let cusp1 = self.params.curvature(1.0) * offset + chord_len;
+        let mut pts = Vec::new();
+        if cusp0 * cusp1 >= 0.0 {
+            crate::flatten::flatten_offset(self, offset, tolerance, |p| pts.push(p));
+        } else {
+            let t_cusp = cusp0 / (cusp0 - cusp1);
+            crate::flatten::flatten_offset_range(self, offset, 0.0, t_cusp, tolerance, |p| {
+                pts.push(p)
+            });
+            pts.push(self.eval_evolute(t_cusp));
+            crate::flatten::flatten_offset_range(self, offset, t_cusp, 1.0, tolerance, |p| {
+                pts.push(p)
+            });
+        }
+        pts.into_iter()
+    }
+
+    /// Flatten the variable-width offset curve `base(t) + w(t)·n(t)` of this
+    /// segment, where `w` is a width profile sampled at the same normalized
+    /// parameter as `eval`/`eval_with_offset`.
+    ///
+    /// `flatten_offset`'s ESPC subdivision density is a closed form derived
+    /// for a *constant* offset; once `w` varies, the offset curve's tangent
+    /// picks up an extra `w'(t)` term that formula doesn't account for, so
+    /// there's no equally cheap analytic density to reuse here. Instead this
+    /// recursively bisects: a candidate interval is accepted once its
+    /// midpoint sample sits within `tolerance` of the straight line between
+    /// its ends *and* `w` varies by less than `tolerance` across it, so a
+    /// fast taper can't be mistaken for a straight run purely because the
+    /// geometry itself is flat. A depth cap bounds pathological (e.g.
+    /// discontinuous) width profiles that would otherwise never settle.
+    ///
+    /// Where `w(t)` crosses zero, both sides' offset points converge on the
+    /// spine; no special-casing is needed for this since the recursive
+    /// sampling walks straight through the crossing like any other point.
+    pub fn flatten_variable_offset(
+        &self,
+        w: impl Fn(f64) -> f64,
+        tolerance: f64,
+    ) -> Vec<Point> {
+        let mut pts = vec![self.eval_with_offset(0.0, w(0.0))];
+        self.flatten_variable_offset_rec(&w, 0.0, 1.0, tolerance, 0, &mut pts);
+        pts
+    }
+
+    fn flatten_variable_offset_rec(
+        &self,
+        w: &impl Fn(f64) -> f64,
+        t0: f64,
+        t1: f64,
+        tolerance: f64,
+        depth: u32,
+        out: &mut Vec<Point>,
+    ) {
+        const MAX_DEPTH: u32 = 24;
+        let w0 = w(t0);
+        let w1 = w(t1);
+        let p0 = self.eval_with_offset(t0, w0);
+        let p1 = self.eval_with_offset(t1, w1);
+        let tm = 0.5 * (t0 + t1);
+        let wm = w(tm);
+        let pm = self.eval_with_offset(tm, wm);
+        let chord = p1 - p0;
+        let chord_len = chord.hypot();
+        let dev = if chord_len > 0.0 {
+            (pm - p0).cross(chord) / chord_len
+        } else {
+            (pm - p0).hypot()
+        };
+        let flat = depth >= MAX_DEPTH || (dev.abs() < tolerance && (w1 - w0).abs() < tolerance);
+        if flat {
+            out.push(p1);
+        } else {
+            self.flatten_variable_offset_rec(w, t0, tm, tolerance, depth + 1, out);
+            self.flatten_variable_offset_rec(w, tm, t1, tolerance, depth + 1, out);
+        }
+    }
+
     /// Calculate error from cubic bezier.
     ///
     /// This is a fairly brute-force technique, sampling the bezier and
@@ -335,25 +545,207 @@ impl EulerSeg {
     /// Note: experimentation suggests that n = 4 is enough to estimate the
     /// error fairly accurately. A future evolution may get rid of that
     /// parameter, and possibly also a tolerance parameter.
+    ///
+    /// Arclength (both the total and each sample's `norm_len` fraction of
+    /// it) is estimated with a single sweep of closed-form 5-point
+    /// Gauss-Lobatto quadrature over the `n + 1` sample intervals, with the
+    /// per-interval lengths prefix-summed, rather than calling
+    /// `ParamCurveArclen::arclen`'s adaptive subdivision from `0` for every
+    /// sample.
     pub fn cubic_euler_err(&self, cubic: CubicBez, n: usize) -> f64 {
-        // One way to improve this would be compute arclengths for each segment
-        // and cumulative sum them, rather than from 0 each time.
-        //
-        // We can also consider Legendre-Gauss quadrature.
-        //
-        // It's likely a rough approximation to arclength will be effective
-        // here, for example LGQ with a low order.
-        let cubic_len = cubic.arclen(1e-9);
+        let dt = 1.0 / (n + 1) as f64;
+        let mut cumulative_len = Vec::with_capacity(n + 1);
+        let mut acc = 0.0;
+        for i in 0..=n {
+            let sub = cubic.subsegment(i as f64 * dt..(i + 1) as f64 * dt);
+            acc += cubic_arclen_quick(sub);
+            cumulative_len.push(acc);
+        }
+        let cubic_len = acc;
         let mut err = 0.0;
         for i in 0..n {
-            let t = (i + 1) as f64 / ((n + 1) as f64);
-            let norm_len = cubic.subsegment(0.0..t).arclen(1e-9) / cubic_len;
+            let t = (i + 1) as f64 * dt;
+            let norm_len = cumulative_len[i] / cubic_len;
             let cubic_xy = cubic.eval(t);
             let euler_xy = self.eval(norm_len);
             err += (cubic_xy - euler_xy).hypot2();
         }
         (err / (n as f64)).sqrt()
     }
+
+    /// Estimate the Fréchet distance between `cubic` and this fitted Euler
+    /// segment, as a sampled heuristic rather than a proven bound (like
+    /// `est_euler_err`).
+    ///
+    /// The Euler segment is sampled at `n + 1` evenly spaced values of its
+    /// own parameter (already an arclength-ish fraction by construction),
+    /// and the cubic at `n + 1` evenly spaced values of `t`; each cubic
+    /// sample's normalized arclength (computed the way `cubic_euler_err`
+    /// does) picks the Euler station it is compared around. For each cubic
+    /// sample, the closest Euler sample is found only within a small window
+    /// of nearby stations, so the correspondence is monotone along the
+    /// curve instead of free to backtrack; the result is the maximum over
+    /// all samples of that closest distance. Because only these discrete
+    /// stations are checked, a cubic that diverges from the Euler fit
+    /// between two adjacent samples can register a smaller error here than
+    /// the true (continuous) Fréchet distance; raising `n` narrows that gap
+    /// but doesn't eliminate it.
+    pub fn frechet_err(&self, cubic: CubicBez, n: usize) -> f64 {
+        /// How many stations on either side of a sample's own index to
+        /// search on the other curve.
+        const WINDOW: usize = 2;
+        let dt = 1.0 / n as f64;
+        let mut cumulative_len = vec![0.0; n + 1];
+        let mut acc = 0.0;
+        for i in 0..n {
+            let t0 = i as f64 * dt;
+            acc += cubic_arclen_quick(cubic.subsegment(t0..t0 + dt));
+            cumulative_len[i + 1] = acc;
+        }
+        let cubic_len = acc.max(1e-12);
+        let euler_pts: Vec<Point> = (0..=n).map(|i| self.eval(i as f64 * dt)).collect();
+        let mut max_err: f64 = 0.0;
+        for i in 0..=n {
+            let cubic_pt = cubic.eval(i as f64 * dt);
+            let norm_len = cumulative_len[i] / cubic_len;
+            let center = ((norm_len * n as f64).round() as isize).clamp(0, n as isize) as usize;
+            let lo = center.saturating_sub(WINDOW);
+            let hi = (center + WINDOW).min(n);
+            let closest = euler_pts[lo..=hi]
+                .iter()
+                .map(|euler_pt| (cubic_pt - *euler_pt).hypot())
+                .fold(f64::INFINITY, f64::min);
+            max_err = max_err.max(closest);
+        }
+        max_err
+    }
+}
+
+/// Closed-form 5-point Gauss-Lobatto estimate of a cubic's arc length.
+///
+/// Lobatto (not Legendre) nodes: the endpoints `t = 0, 1` are included among
+/// the five sample points, unlike Gauss-Legendre's interior-only nodes. The
+/// quadrature abscissas and weights for a cubic's (quadratic) derivative
+/// are folded directly into these coefficients on the control points, so
+/// this is a fixed amount of work rather than `ParamCurveArclen::arclen`'s
+/// adaptive subdivision. Good enough for the sampling `cubic_euler_err` does
+/// over short sub-intervals; not a substitute for `arclen`'s tolerance
+/// guarantee on an arbitrary cubic.
+fn cubic_arclen_quick(c: CubicBez) -> f64 {
+    let p0 = c.p0.to_vec2();
+    let p1 = c.p1.to_vec2();
+    let p2 = c.p2.to_vec2();
+    let p3 = c.p3.to_vec2();
+    let v0 = 0.15 * (p1 - p0).hypot();
+    let v1 = (-0.558983582205757 * p0
+        + 0.325650248872424 * p1
+        + 0.208983582205757 * p2
+        + 0.024349751127576 * p3)
+        .hypot();
+    let v2 = 0.26666666666666666 * (p3 - p0 + (p2 - p1)).hypot();
+    let v3 = (-0.024349751127576 * p0
+        - 0.208983582205757 * p1
+        - 0.325650248872424 * p2
+        + 0.558983582205757 * p3)
+        .hypot();
+    let v4 = 0.15 * (p3 - p2).hypot();
+    v0 + v1 + v2 + v3 + v4
+}
+
+/// How a cubic's curvature changes sign across its span.
+///
+/// A cubic has either two real inflection parameters (`DoubleInflection`,
+/// the generic case) or none, in which case it instead loops back on
+/// itself (`Loop`); `classify_cusp` tells these apart from the sign of the
+/// inflection parameter's discriminant, so callers can handle a near-cusp
+/// Euler fit differently depending on which shape they're near.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CuspKind {
+    /// The curve crosses itself; the inflection-parameter quadratic has
+    /// complex roots.
+    Loop,
+    /// Two distinct, real inflection parameters.
+    DoubleInflection,
+}
+
+/// Classify a cubic by the discriminant of its inflection-point polynomial.
+///
+/// Writing the cubic as `p0 + 3*a*t + 3*b*t^2 + c*t^3` (with `a = p1 - p0`,
+/// `b = p0 - 2*p1 + p2`, `c = -p0 + 3*p1 - 3*p2 + p3`), the parameters where
+/// curvature changes sign are the roots of
+/// `cross(b, c)*t^2 + cross(a, c)*t + cross(a, b) = 0`
+/// (from `cross(B'(t), B''(t)) = 0`, expanded and divided by the common
+/// factor of 18). Two real roots mean the curve has two distinct
+/// inflections; complex roots mean it loops instead.
+pub fn classify_cusp(c: CubicBez) -> CuspKind {
+    let p0 = c.p0.to_vec2();
+    let p1 = c.p1.to_vec2();
+    let p2 = c.p2.to_vec2();
+    let p3 = c.p3.to_vec2();
+    let a = p1 - p0;
+    let b = p0 - 2.0 * p1 + p2;
+    let cubic_c = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let cross_ab = a.cross(b);
+    let cross_ac = a.cross(cubic_c);
+    let cross_bc = b.cross(cubic_c);
+    let qa = cross_bc;
+    let qb = cross_ac;
+    let qc = cross_ab;
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if discriminant < 0.0 {
+        CuspKind::Loop
+    } else {
+        CuspKind::DoubleInflection
+    }
+}
+
+/// Scale factor (relative to a curve's overall `dimension`) below which a
+/// chord or tangent is treated as degenerate by [`regularize`] and by
+/// `CubicToEulerIter::next`'s sub-chord guard.
+const REGULARIZE_EPS: f64 = 1e-9;
+
+/// Perturb near-coincident cubic control points so the chord/tangent
+/// quantities that feed Euler fitting, offsetting and flattening never have
+/// to divide by a near-zero vector.
+///
+/// `dimension` is the curve's overall scale (for example, its chord or
+/// bounding-box diagonal); the perturbation is sized tiny relative to it but
+/// well outside float noise. Each offending control point is nudged along
+/// the nearest available tangent rather than an arbitrary direction, so the
+/// regularized curve still resembles the intent of the original.
+pub fn regularize(c: CubicBez, dimension: f64) -> CubicBez {
+    let eps = dimension.max(1.0) * REGULARIZE_EPS;
+    let mut p0 = c.p0;
+    let mut p1 = c.p1;
+    let mut p2 = c.p2;
+    let mut p3 = c.p3;
+    if (p1 - p0).hypot() < eps {
+        let tangent = if (p2 - p0).hypot() >= eps {
+            p2 - p0
+        } else {
+            p3 - p0
+        };
+        if tangent.hypot() >= eps {
+            p1 = p0 + eps * (tangent / tangent.hypot());
+        }
+    }
+    if (p3 - p2).hypot() < eps {
+        let tangent = if (p3 - p1).hypot() >= eps {
+            p3 - p1
+        } else {
+            p3 - p0
+        };
+        if tangent.hypot() >= eps {
+            p2 = p3 - eps * (tangent / tangent.hypot());
+        }
+    }
+    if (p3 - p0).hypot() < eps {
+        let tangent = p1 - p0;
+        if tangent.hypot() >= eps {
+            p3 = p0 + eps * (tangent / tangent.hypot());
+        }
+    }
+    CubicBez::new(p0, p1, p2, p3)
 }
 
 /// Evaluate point and derivative.
@@ -378,6 +770,13 @@ pub fn eval_and_deriv(c: &CubicBez, t: f64) -> (Point, Vec2) {
 const DERIV_THRESH: f64 = 1e-6;
 /// Amount to nudge t when derivative is near-zero.
 const DERIV_EPS: f64 = 1e-6;
+/// Sample count `next` passes to `EulerSeg::frechet_err` when
+/// `ErrorMetric::Frechet` is selected.
+const FRECHET_SAMPLES: usize = 8;
+/// `dt` threshold below which `next` stops subdividing a `CuspKind::Loop`
+/// sub-range and accepts the fit as-is, since a genuine loop cusp's error
+/// never shrinks no matter how far it's subdivided.
+const MIN_CUSP_DT: f64 = 1e-6;
 
 impl Iterator for CubicToEulerIter {
     type Item = EulerSeg;
@@ -400,16 +799,52 @@ impl Iterator for CubicToEulerIter {
                     t1 = t1 - DERIV_EPS;
                 }
             }
+            // A cusp can land exactly on a subdivision boundary, collapsing
+            // this sub-range's chord to near zero even though the curve as
+            // a whole was regularized in `new`; nudge along the local
+            // tangent the same way `regularize` would, so the chord-based
+            // normalization below doesn't divide by it. `classify_cusp`
+            // below further distinguishes a genuine self-crossing loop from
+            // a double-inflection near-miss once subdivision alone can't
+            // bring the error under tolerance.
+            let chord_eps = self.dimension.max(1.0) * REGULARIZE_EPS;
+            if (p1 - p0).hypot() < chord_eps {
+                let tangent = if q1.hypot2() >= DERIV_THRESH.powi(2) {
+                    q1
+                } else {
+                    q0
+                };
+                if tangent.hypot2() >= DERIV_THRESH.powi(2) {
+                    p1 = p0 + tangent / tangent.hypot() * chord_eps;
+                }
+            }
             let actual_dt = t1 - self.last_t;
             let cubic_params = CubicParams::from_points_derivs(p0, p1, q0, q1, actual_dt);
-            let est_err = cubic_params.est_euler_err();
-            let err = est_err * (p1 - p0).hypot();
-            if err <= self.tolerance {
+            let euler_params = EulerParams::from_angles(cubic_params.th0, cubic_params.th1);
+            let err = match self.err_metric {
+                ErrorMetric::Heuristic => cubic_params.est_euler_err() * (p1 - p0).hypot(),
+                ErrorMetric::Frechet => {
+                    let es = EulerSeg::from_params(p0, p1, euler_params);
+                    es.frechet_err(self.c.subsegment(t0..t1), FRECHET_SAMPLES)
+                }
+            };
+            // A genuine `Loop` cusp (the sub-curve crosses itself) never
+            // converges by subdividing further: every sub-chord through the
+            // cusp still reverses direction, so the fit error stays above
+            // tolerance no matter how small `dt` gets. A
+            // `DoubleInflection` near-miss, by contrast, does resolve into
+            // two well-behaved segments as we keep splitting, so only cut
+            // the subdivision short for an actual loop, and only once `dt`
+            // is already small enough that we're confident we're looking at
+            // the cusp itself rather than coarse sampling.
+            let force_accept = err > self.tolerance
+                && self.dt < MIN_CUSP_DT
+                && classify_cusp(self.c.subsegment(t0..t1)) == CuspKind::Loop;
+            if err <= self.tolerance || force_accept {
                 self.t0 += 1;
                 let shift = self.t0.trailing_zeros();
                 self.t0 >>= shift;
                 self.dt *= (1 << shift) as f64;
-                let euler_params = EulerParams::from_angles(cubic_params.th0, cubic_params.th1);
                 let es = EulerSeg::from_params(p0, p1, euler_params);
                 self.last_p = p1;
                 self.last_q = q1;
@@ -424,6 +859,19 @@ impl Iterator for CubicToEulerIter {
 
 impl CubicToEulerIter {
     pub fn new(c: CubicBez, tolerance: f64) -> Self {
+        Self::new_opt(c, tolerance, ErrorMetric::default())
+    }
+
+    /// Like `new`, but with explicit control over which error estimate
+    /// `next` uses to accept or subdivide a candidate fit.
+    pub fn new_opt(c: CubicBez, tolerance: f64, err_metric: ErrorMetric) -> Self {
+        let dimension = c
+            .p0
+            .distance(c.p1)
+            .max(c.p1.distance(c.p2))
+            .max(c.p2.distance(c.p3))
+            .max(c.p0.distance(c.p3));
+        let c = regularize(c, dimension);
         let last_p = c.p0;
         let mut last_q = c.p1 - c.p0;
         if last_q.hypot2() < DERIV_THRESH.powi(2) {
@@ -432,6 +880,8 @@ impl CubicToEulerIter {
         CubicToEulerIter {
             c,
             tolerance,
+            dimension,
+            err_metric,
             t0: 0,
             dt: 1.0,
             last_p,
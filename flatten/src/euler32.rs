@@ -5,7 +5,8 @@
 //! For now, convert from kurbo-compatible. We'll build up robust
 //! 32-bit conversion from Beziers.
 
-use crate::cubic32::{Cubic, Point};
+use crate::cubic32::{approx_parabola_integral, approx_parabola_inv_integral, Cubic, Point};
+use crate::ops32;
 
 #[derive(Debug)]
 pub struct CubicParams {
@@ -74,8 +75,8 @@ impl CubicParams {
     // by chord.
     pub fn est_euler_err(&self) -> f32 {
         // Potential optimization: work with unit vector rather than angle
-        let cth0 = self.th0.cos();
-        let cth1 = self.th1.cos();
+        let cth0 = ops32::cos(self.th0);
+        let cth1 = ops32::cos(self.th1);
         if cth0 * cth1 < 0.0 {
             // Rationale: this happens when fitting a cusp or near-cusp with
             // a near 180 degree u-turn. The actual ES is bounded in that case.
@@ -84,8 +85,8 @@ impl CubicParams {
         }
         let e0 = (2. / 3.) / (1.0 + cth0);
         let e1 = (2. / 3.) / (1.0 + cth1);
-        let s0 = self.th0.sin();
-        let s1 = self.th1.sin();
+        let s0 = ops32::sin(self.th0);
+        let s1 = ops32::sin(self.th1);
         // Note: some other versions take sin of s0 + s1 instead. Those are incorrect.
         // Strangely, calibration is the same, but more work could be done.
         //let s01 = (self.th0 + self.th1).sin();
@@ -95,8 +96,8 @@ impl CubicParams {
         let aerr = (a - amin).abs();
         let symm = (self.th0 + self.th1).abs();
         let asymm = (self.th0 - self.th1).abs();
-        let dist = (self.d0 - e0).hypot(self.d1 - e1);
-        let ctr = 4.625e-6 * symm.powi(5) + 7.5e-3 * asymm * symm.powi(2);
+        let dist = ops32::hypot(self.d0 - e0, self.d1 - e1);
+        let ctr = 4.625e-6 * ops32::powi(symm, 5) + 7.5e-3 * asymm * ops32::powi(symm, 2);
         let halo_symm = 5e-3 * symm * dist;
         let halo_asymm = 7e-2 * asymm * dist;
         println!("ctr={ctr}, aerr={aerr}, halo_symm={halo_symm}, halo_asymm={halo_asymm}");
@@ -163,8 +164,8 @@ impl EulerParams {
         let k0 = self.k0;
         let k1 = self.k1;
         let (u, v) = integ_euler_10((k0 + k1 * (0.5 * t - 0.5)) * t, k1 * t * t);
-        let s = t / self.ch * thm.sin();
-        let c = t / self.ch * thm.cos();
+        let s = t / self.ch * ops32::sin(thm);
+        let c = t / self.ch * ops32::cos(thm);
         let x = u * c - v * s;
         let y = -v * c - u * s;
         Point::new(x, y)
@@ -173,7 +174,7 @@ impl EulerParams {
     pub fn eval_with_offset(&self, t: f32, offset: f32) -> Point {
         let th = self.eval_th(t);
         let p = self.eval(t);
-        Point::new(p.x + offset * th.sin(), p.y + offset * th.cos())
+        Point::new(p.x + offset * ops32::sin(th), p.y + offset * ops32::cos(th))
     }
 }
 
@@ -199,6 +200,49 @@ impl EulerSeg {
             self.p0.y + chord.x * y + chord.y * x,
         )
     }
+
+    /// Flatten this (optionally offset) segment into near-minimal line
+    /// segments, returning the sampled points (the segment's own start
+    /// point, `eval_with_offset(0.0, offset)`, is not included).
+    ///
+    /// This is the CPU analogue of the GPU flattening path benchmarked by
+    /// this paper: curvature `k0`/`k1` is mapped through
+    /// `approx_parabola_integral` (the same parabola-integral
+    /// reparameterization `Cubic::flatten` uses) to get endpoints `a0`/`a2`
+    /// in parabola-arc-parameter space; the point count `n` is sized from
+    /// `sqrt(scale / tolerance)`, and each sample's `t` is recovered from an
+    /// evenly spaced parameter value via `approx_parabola_inv_integral`.
+    pub fn flatten(&self, tolerance: f32, offset: f32) -> Vec<Point> {
+        let k0 = self.params.k0 - 0.5 * self.params.k1;
+        let k1 = self.params.k1;
+        let chord = (self.p1 - self.p0).hypot() / self.params.ch;
+        let a0 = approx_parabola_integral(k0);
+        let a2 = approx_parabola_integral(k0 + k1);
+        let scale = (a2 - a0).abs();
+        // Near-constant-curvature limit: k1 is (nearly) zero, so a0 and a2
+        // coincide and the parabola mapping degenerates. Fall back to even
+        // spacing.
+        let n = if scale < 1e-6 {
+            let k = 0.5 * (k0 + k0 + k1);
+            (0.25 * k.abs() * chord * chord / tolerance).sqrt()
+        } else {
+            0.5 * scale * (scale * chord / tolerance).sqrt()
+        }
+        .ceil()
+        .max(1.0) as usize;
+        (1..=n)
+            .map(|i| {
+                let u = i as f32 / n as f32;
+                let t = if scale < 1e-6 {
+                    u
+                } else {
+                    let a = a0 + (a2 - a0) * u;
+                    (approx_parabola_inv_integral(a) - k0) / k1
+                };
+                self.eval_with_offset(t, offset)
+            })
+            .collect()
+    }
 }
 
 impl Iterator for CubicToEulerIter {
@@ -307,3 +351,38 @@ fn integ_euler_10(k0: f32, k1: f32) -> (f32, f32) {
     v -= (1. / 11612160.) * t7_8;
     (u, v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `EulerParams::from_f64` plus the f32 `eval`/`eval_with_offset` path
+    /// should stay close to the f64 reference in `crate::euler` for the
+    /// same angles, since that agreement (not just internal self-
+    /// consistency) is the whole point of having an f32 path at all.
+    #[test]
+    fn f32_eval_matches_f64_reference() {
+        const EPS: f32 = 1e-4;
+        for &(th0, th1) in &[(0.3, 0.5), (-0.2, 0.7), (0.05, 0.05), (1.0, -0.8)] {
+            let ep64 = crate::euler::EulerParams::from_angles(th0, th1);
+            let ep32 = EulerParams::from_f64(&ep64);
+            for i in 0..=10 {
+                let t = i as f64 / 10.0;
+                let p64 = ep64.eval_with_offset(t, 0.1);
+                let p32 = ep32.eval_with_offset(t as f32, 0.1);
+                assert!(
+                    (p32.x - p64.x as f32).abs() < EPS,
+                    "x mismatch at th0={th0} th1={th1} t={t}: f32={} f64={}",
+                    p32.x,
+                    p64.x
+                );
+                assert!(
+                    (p32.y - p64.y as f32).abs() < EPS,
+                    "y mismatch at th0={th0} th1={th1} t={t}: f32={} f64={}",
+                    p32.y,
+                    p64.y
+                );
+            }
+        }
+    }
+}
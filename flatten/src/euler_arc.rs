@@ -5,7 +5,7 @@
 
 use std::f64::consts::FRAC_PI_2;
 
-use kurbo::{common::GAUSS_LEGENDRE_COEFFS_32, Vec2};
+use kurbo::{common::GAUSS_LEGENDRE_COEFFS_32, BezPath, Vec2};
 
 use crate::euler::{EulerParams, EulerSeg};
 
@@ -116,6 +116,67 @@ fn espc_to_arc_k(params: &EulerParams, d: f64) -> f64 {
     ((s * s + 6.0 * d * d) * k0 + 12. * s * d - 12. * aq) / (s + d * k0).powi(2)
 }
 
+/// Fit an `EulerSeg` through the original (un-offset) curve `es` restricted
+/// to `t0..t1`, by G1 Hermite interpolation to the new chord and `es`'s own
+/// tangent directions at `t0` and `t1` — the same construction
+/// `EulerSeg::from_cubic` uses to fit a cubic's endpoints and tangents.
+fn sub_euler_seg(es: &EulerSeg, t0: f64, t1: f64) -> EulerSeg {
+    let p0 = es.eval(t0);
+    let p1 = es.eval(t1);
+    let chord = p1 - p0;
+    let angle0 = (es.p1 - es.p0).atan2();
+    let tan0 = Vec2::from_angle(angle0 - es.params.eval_th(t0));
+    let tan1 = Vec2::from_angle(angle0 - es.params.eval_th(t1));
+    let th0 = chord.cross(tan0).atan2(chord.dot(tan0));
+    let th1 = tan1.cross(chord).atan2(tan1.dot(chord));
+    let params = EulerParams::from_angles(th0, th1);
+    EulerSeg::from_params(p0, p1, params)
+}
+
+/// Depth limit for the bisection in `lower_seg_to_arcs`, as a backstop
+/// against runaway recursion near cusps where the fit can't converge.
+const MAX_ARC_SPLIT_DEPTH: u32 = 24;
+
+fn lower_seg_to_arcs(es: &EulerSeg, offset: f64, t0: f64, t1: f64, tol: f64, depth: u32, path: &mut BezPath) {
+    let sub = sub_euler_seg(es, t0, t1);
+    let chord_len = (sub.p1 - sub.p0).hypot();
+    let d = offset / chord_len;
+    let k = espc_to_arc_k(&sub.params, d);
+    let err = espc_arc_error(&sub.params, d, k) * chord_len;
+    if err <= tol || depth == 0 {
+        let arc_params = EulerParams::from_angles(0.5 * k, 0.5 * k);
+        let p0 = sub.eval_with_offset(0.0, offset);
+        let p1 = sub.eval_with_offset(1.0, offset);
+        let arc_seg = EulerSeg::from_params(p0, p1, arc_params);
+        let cubic = arc_seg.to_cubic();
+        path.curve_to(cubic.p1, cubic.p2, cubic.p3);
+    } else {
+        let tm = 0.5 * (t0 + t1);
+        lower_seg_to_arcs(es, offset, t0, tm, tol, depth - 1, path);
+        lower_seg_to_arcs(es, offset, tm, t1, tol, depth - 1, path);
+    }
+}
+
+/// Lower a stream of Euler-spiral parallel-curve segments to a circular-arc
+/// spline, within `tol` of the true offset curve at distance `offset`.
+///
+/// Each segment's parameter range is recursively bisected, fitting the best
+/// circular arc (`espc_to_arc_k`) and measuring its error against the offset
+/// strip (`espc_arc_error`), until the error is within `tol`. This gives an
+/// arc-based alternative to `flatten::flatten_offset`'s line segments, with
+/// far fewer segments for low-curvature strokes.
+pub fn lower_to_arcs(iter: impl Iterator<Item = EulerSeg>, offset: f64, tol: f64) -> BezPath {
+    let mut path = BezPath::new();
+    let mut first = true;
+    for es in iter {
+        if core::mem::take(&mut first) {
+            path.move_to(es.eval_with_offset(0.0, offset));
+        }
+        lower_seg_to_arcs(&es, offset, 0.0, 1.0, tol, MAX_ARC_SPLIT_DEPTH, &mut path);
+    }
+    path
+}
+
 #[allow(unused)]
 fn arc_main_err() {
     let kmean = 1.0;
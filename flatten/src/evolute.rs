@@ -8,6 +8,7 @@ use kurbo::{Arc, BezPath};
 use crate::{
     arc_segment::ArcSegment,
     euler::{EulerParams, EulerSeg},
+    flatten::flatten_offset_parabola,
 };
 
 /// Flatten the evolute of an Euler spiral segment.
@@ -71,20 +72,14 @@ pub fn lower_es_evolute_arc(es: &EulerSeg, tol: f64) -> BezPath {
 
 /// Flatten an Euler spiral segment.
 ///
-/// This is duplicative of other work but maybe useful for exploration.
-pub fn flatten_es(es: &EulerSeg, _tol: f64) -> BezPath {
+/// This is duplicative of other work but maybe useful for exploration. Reuses
+/// the parabola-integral subdivision density from [`flatten_offset_parabola`]
+/// at `offset = 0`, so spacing is near-uniform in flattening error rather than
+/// a fixed subdivision count.
+pub fn flatten_es(es: &EulerSeg, tol: f64) -> BezPath {
     let mut path = BezPath::new();
-    // TODO: smart flatten logic
-    const N: usize = 10;
-    for i in 0..=N {
-        let t = i as f64 / N as f64;
-        let p = es.eval(t);
-        if i == 0 {
-            path.move_to(p);
-        } else {
-            path.line_to(p)
-        }
-    }
+    path.move_to(es.eval(0.0));
+    flatten_offset_parabola(es, 0.0, tol, |p| path.line_to(p));
     path
 }
 
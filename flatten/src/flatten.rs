@@ -5,7 +5,7 @@
 
 use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4};
 
-use kurbo::{BezPath, Point};
+use kurbo::{BezPath, Point, Vec2};
 
 use crate::euler::EulerSeg;
 
@@ -85,6 +85,139 @@ pub fn flatten_offset(es: &EulerSeg, offset: f64, tol: f64, mut f: impl FnMut(Po
     f(es.eval_with_offset(1.0, offset));
 }
 
+/// One step of the dash walk done by `flatten_offset_dashed`.
+pub enum DashEvent {
+    /// The start of a new "on" dash; the caller should treat this as a
+    /// `move_to` for whatever comes next.
+    NewDash,
+    /// A point along the current "on" dash's offset curve.
+    Point(Point),
+    /// A zero-length "on" dash (an SVG "dot"): there's no arc length to walk
+    /// for it, so its position and the curve's tangent direction there are
+    /// reported directly instead of a run of points.
+    Dot(Point, Vec2),
+}
+
+/// Flatten an Euler spiral parallel curve to lines, applying a dash pattern.
+///
+/// Walks `iter` in arc-length order, toggling pen-up/pen-down according to
+/// `dash_array` (alternating on/off lengths, in the same user units as the
+/// curve) starting at `dash_phase` into the pattern. `f` is invoked with
+/// `DashEvent::NewDash` to mark the start of a new dash, `DashEvent::Point`
+/// for each subsequent point on that dash, and `DashEvent::Dot` for a
+/// zero-length dash.
+///
+/// The total pattern length (sum of `dash_array`) must be positive.
+pub fn flatten_offset_dashed(
+    iter: impl Iterator<Item = EulerSeg>,
+    offset: f64,
+    dash_array: &[f64],
+    dash_phase: f64,
+    tol: f64,
+    mut f: impl FnMut(DashEvent),
+) {
+    let pattern_len: f64 = dash_array.iter().sum();
+    if !(pattern_len > 0.0) {
+        panic!("flatten_offset_dashed: dash pattern length must be positive");
+    }
+    // Index into dash_array and the remaining length of the current dash entry.
+    let mut phase = dash_phase.rem_euclid(pattern_len);
+    let mut dash_ix = 0;
+    while phase >= dash_array[dash_ix] {
+        phase -= dash_array[dash_ix];
+        dash_ix = (dash_ix + 1) % dash_array.len();
+    }
+    let mut remaining = dash_array[dash_ix] - phase;
+    // Dash indices are even for "on" and odd for "off", by SVG convention.
+    let mut pen_down = dash_ix % 2 == 0;
+    let mut dash_started = false;
+    for es in iter {
+        let arclen = (es.p0 - es.p1).hypot() / es.params.ch;
+        let chord = es.p1 - es.p0;
+        let tangent_at = |t: f64| -> Vec2 {
+            let (ls, lc) = es.params.eval_th(t).sin_cos();
+            // The local tangent angle is `-eval_th(t)` relative to the chord.
+            Vec2::new(chord.x * lc + chord.y * ls, chord.y * lc - chord.x * ls)
+        };
+        let mut s0 = 0.0;
+        loop {
+            let s_left = 1.0 - s0;
+            let remaining_s = remaining / arclen;
+            if remaining_s >= s_left {
+                // The rest of this segment is a single dash state.
+                if pen_down {
+                    if !dash_started {
+                        f(DashEvent::NewDash);
+                        f(DashEvent::Point(es.eval_with_offset(s0, offset)));
+                        dash_started = true;
+                    }
+                    if s0 == 0.0 {
+                        flatten_offset(&es, offset, tol, |p| f(DashEvent::Point(p)));
+                    } else {
+                        flatten_offset_range(&es, offset, s0, 1.0, tol, |p| {
+                            f(DashEvent::Point(p))
+                        });
+                    }
+                }
+                remaining -= s_left * arclen;
+                break;
+            }
+            let s1 = s0 + remaining_s;
+            if pen_down {
+                if !dash_started {
+                    f(DashEvent::NewDash);
+                    f(DashEvent::Point(es.eval_with_offset(s0, offset)));
+                    dash_started = true;
+                }
+                flatten_offset_range(&es, offset, s0, s1, tol, |p| f(DashEvent::Point(p)));
+                dash_started = false;
+            }
+            s0 = s1;
+            dash_ix = (dash_ix + 1) % dash_array.len();
+            pen_down = !pen_down;
+            remaining = dash_array[dash_ix];
+            // Zero-length "on" dashes are SVG dots: report them directly
+            // since there's no arc length to walk. Zero-length "off" dashes
+            // just toggle straight through with nothing to draw.
+            while remaining == 0.0 {
+                if pen_down {
+                    f(DashEvent::NewDash);
+                    f(DashEvent::Dot(es.eval_with_offset(s0, offset), tangent_at(s0)));
+                }
+                dash_ix = (dash_ix + 1) % dash_array.len();
+                pen_down = !pen_down;
+                remaining = dash_array[dash_ix];
+            }
+        }
+    }
+}
+
+/// Flatten an Euler spiral parallel curve over a sub-range `[s0, s1]` of its parameter.
+pub(crate) fn flatten_offset_range(
+    es: &EulerSeg,
+    offset: f64,
+    s0: f64,
+    s1: f64,
+    tol: f64,
+    mut f: impl FnMut(Point),
+) {
+    // Reuse flatten_offset's subdivision logic by sampling it is not directly
+    // exposed for sub-ranges, so fall back to a simple analytic-density walk.
+    let n = (n_subdiv_analytic(
+        es.params.k0 - 0.5 * es.params.k1,
+        es.params.k1,
+        (es.p0 - es.p1).hypot() / es.params.ch,
+        offset,
+        tol,
+    ) * (s1 - s0).abs())
+    .ceil()
+    .max(1.0) as usize;
+    for i in 1..=n {
+        let t = s0 + (s1 - s0) * (i as f64) / (n as f64);
+        f(es.eval_with_offset(t, offset));
+    }
+}
+
 pub fn flatten_offset_iter(iter: impl Iterator<Item = EulerSeg>, offset: f64) -> BezPath {
     let mut result = BezPath::new();
     let tol = 1.0;
@@ -211,3 +344,133 @@ fn espc_int_inv_approx(x: f64) -> f64 {
     };
     a.copysign(x)
 }
+
+/// Approximate the arc-length integral of a unit parabola with tangent
+/// angle `x`, per Raph Levien's parabola approximation to flattening (the
+/// same one `kurbo::flatten` uses for generic cubics). Used below to map an
+/// Euler segment's tangent angles into a pseudo-arc-length space, as a
+/// cheaper alternative to the ESPC integral above.
+fn approx_parabola_integral(x: f64) -> f64 {
+    const D: f64 = 0.67;
+    let quarter_x2 = 0.25 * x * x;
+    x / (1.0 - D + (D.powi(4) + quarter_x2).sqrt().sqrt())
+}
+
+/// Inverse of `approx_parabola_integral`.
+fn approx_parabola_inv_integral(x: f64) -> f64 {
+    const B: f64 = 0.39;
+    x * (1.0 - B + (B * B + 0.25 * x * x).sqrt())
+}
+
+/// Flatten an Euler spiral parallel curve to lines, like `flatten_offset`,
+/// but size the subdivision count in closed form from the segment's
+/// tangent angles rather than from the ESPC curvature integral.
+///
+/// The tangent angle at each endpoint, taken relative to the midpoint angle
+/// so the range is centered on zero like the parabola approximation
+/// expects, is mapped through `approx_parabola_integral` into a pseudo
+/// arc-length parameter; the difference scaled by `sqrt(scale / tol)` gives
+/// a fractional subdivision count `n`. Samples are then placed at evenly
+/// spaced values of that parameter, mapped back to an angle via
+/// `approx_parabola_inv_integral` and from there to a curve `t` via
+/// `EulerParams::inv_th`. This gives near-uniform error in about the same
+/// closed-form, constant-time shape as `flatten_offset`, at the cost of a
+/// cruder curvature model; it's offered here as a cheaper alternative, not
+/// a replacement for the tuned ESPC path above.
+pub fn flatten_offset_parabola(es: &EulerSeg, offset: f64, tol: f64, mut f: impl FnMut(Point)) {
+    let th0 = es.params.eval_th(0.0);
+    let th1 = es.params.eval_th(1.0);
+    let mid = 0.5 * (th0 + th1);
+    let chord = (es.p0 - es.p1).hypot() / es.params.ch;
+    let x0 = approx_parabola_integral(th0 - mid);
+    let x1 = approx_parabola_integral(th1 - mid);
+    let scale = (x1 - x0).abs();
+    // Low-curvature limit: th0 and th1 (nearly) coincide, so the parabola
+    // mapping degenerates. Fall back to even spacing driven directly by the
+    // segment's curvature at the midpoint.
+    let n = if scale < 1e-9 {
+        let k = es.params.curvature(0.5).abs();
+        (0.25 * k * chord * chord / tol).sqrt()
+    } else {
+        0.5 * scale * (scale * chord / tol).sqrt()
+    }
+    .ceil()
+    .max(1.0) as usize;
+    for i in 1..=n {
+        let u = i as f64 / n as f64;
+        let t = if scale < 1e-9 {
+            u
+        } else {
+            let x = x0 + (x1 - x0) * u;
+            let th = approx_parabola_inv_integral(x) + mid;
+            es.params
+                .inv_th(th)
+                .into_iter()
+                .find(|&t| (0.0..=1.0).contains(&t))
+                .unwrap_or(u)
+        };
+        f(es.eval_with_offset(t, offset));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{CubicBez, Point};
+
+    use super::*;
+
+    /// `flatten_offset_parabola` should, like `flatten_offset`, emit the
+    /// curve's true end point last (it documents "includes the end point
+    /// but not the start point"), and should subdivide a visibly curved
+    /// segment into more than one line rather than drawing it as a single
+    /// chord.
+    #[test]
+    fn parabola_flatten_reaches_end_point_and_subdivides() {
+        let c = CubicBez::new(
+            Point::new(0.0, 0.0),
+            Point::new(0.1, 0.6),
+            Point::new(0.9, 0.6),
+            Point::new(1.0, 0.0),
+        );
+        let es = EulerSeg::from_cubic(c);
+        let offset = 0.05;
+        let tol = 1e-3;
+        let expected_end = es.eval_with_offset(1.0, offset);
+
+        let mut pts = Vec::new();
+        flatten_offset_parabola(&es, offset, tol, |p| pts.push(p));
+
+        assert!(!pts.is_empty());
+        let last = *pts.last().unwrap();
+        assert!(
+            (last - expected_end).hypot() < 1e-9,
+            "last point {last:?} should match the curve's true end {expected_end:?}"
+        );
+        assert!(
+            pts.len() > 1,
+            "a visibly curved segment should need more than one line to flatten within tolerance"
+        );
+    }
+
+    /// A straight (zero-curvature) segment hits the low-curvature fallback
+    /// branch (`scale < 1e-9`), which should still land exactly on the end
+    /// point via plain even spacing.
+    #[test]
+    fn parabola_flatten_straight_segment_fallback() {
+        let c = CubicBez::new(
+            Point::new(0.0, 0.0),
+            Point::new(1.0 / 3.0, 0.0),
+            Point::new(2.0 / 3.0, 0.0),
+            Point::new(1.0, 0.0),
+        );
+        let es = EulerSeg::from_cubic(c);
+        let expected_end = es.eval_with_offset(1.0, 0.0);
+
+        let mut pts = Vec::new();
+        flatten_offset_parabola(&es, 0.0, 1e-3, |p| pts.push(p));
+
+        assert!(!pts.is_empty());
+        let last = *pts.last().unwrap();
+        assert!((last - expected_end).hypot() < 1e-9);
+    }
+}
@@ -10,6 +10,7 @@ use kurbo::BezPath;
 
 use crate::cubic32::Point;
 use crate::euler32::EulerSeg;
+use crate::ops32;
 
 /// A robustness strategy for the ESPC integral
 enum EspcRobust {
@@ -25,22 +26,73 @@ fn to_kurbo(p: Point) -> kurbo::Point {
     kurbo::Point::new(p.x as f64, p.y as f64)
 }
 
-// Note: generates a kurbo-compatible BezPath for debugging reasons, but
-// actual output can be f32 LineSoup without much trouble.
+/// A destination for the line segments produced by flattening, so that a
+/// GPU-bound caller can collect them directly as `LineSoup` instead of
+/// paying for a `BezPath`.
+pub trait LineSink {
+    /// Record one line segment of the path `path_ix`, from `p0` to `p1`.
+    fn line(&mut self, path_ix: u32, p0: Point, p1: Point);
+}
+
+/// A flat buffer of flattened line segments, tagged by path, ready to upload
+/// to the GPU.
+#[derive(Clone, Copy, Debug)]
+pub struct LineSoup {
+    pub p0: [f32; 2],
+    pub p1: [f32; 2],
+    pub path_ix: u32,
+}
+
+impl LineSink for Vec<LineSoup> {
+    fn line(&mut self, path_ix: u32, p0: Point, p1: Point) {
+        self.push(LineSoup {
+            p0: [p0.x, p0.y],
+            p1: [p1.x, p1.y],
+            path_ix,
+        });
+    }
+}
+
+/// Adapts a `BezPath` to `LineSink`, for callers (and tests) that still want
+/// the debug polyline rather than raw `LineSoup`.
+struct BezPathSink<'a>(&'a mut BezPath, bool);
+
+impl LineSink for BezPathSink<'_> {
+    fn line(&mut self, _path_ix: u32, p0: Point, p1: Point) {
+        if core::mem::take(&mut self.1) {
+            self.0.move_to(to_kurbo(p0));
+        }
+        self.0.line_to(to_kurbo(p1));
+    }
+}
+
 pub fn flatten_offset(iter: impl Iterator<Item = EulerSeg>, offset: f32, tol: f32) -> BezPath {
     let mut result = BezPath::new();
-    let mut first = true;
+    flatten_offset_to(iter, offset, tol, 0, &mut BezPathSink(&mut result, true));
+    result
+}
+
+/// Flatten an Euler spiral parallel curve to line segments, emitting each one
+/// to `sink` instead of building a `BezPath`. This is the GPU-matching
+/// reference path: `sink` can be a `LineSoup` collector so the output is
+/// ready to upload without an intermediate path representation.
+pub fn flatten_offset_to(
+    iter: impl Iterator<Item = EulerSeg>,
+    offset: f32,
+    tol: f32,
+    path_ix: u32,
+    sink: &mut impl LineSink,
+) {
     for es in iter {
-        if core::mem::take(&mut first) {
-            result.move_to(to_kurbo(es.eval_with_offset(0.0, offset)));
-        }
-        let scale = (es.p0 - es.p1).hypot();
+        let mut prev = es.eval_with_offset(0.0, offset);
+        let scale = ops32::hypot(es.p0.x - es.p1.x, es.p0.y - es.p1.y);
         let (k0, k1) = (es.params.k0 - 0.5 * es.params.k1, es.params.k1);
         // Note: scaling by ch is missing from earlier implementations. The math
         // should be validated carefully.
         let dist_scaled = offset * es.params.ch / scale;
         // The number of subdivisions for curvature = 1
-        let scale_multiplier = 0.5 * FRAC_1_SQRT_2 * (scale / (es.params.ch * tol)).sqrt();
+        let scale_multiplier =
+            0.5 * FRAC_1_SQRT_2 * ops32::sqrt(scale / (es.params.ch * tol));
         // TODO: tune these thresholds
         const K1_THRESH: f32 = 1e-3;
         const DIST_THRESH: f32 = 1e-3;
@@ -50,10 +102,10 @@ pub fn flatten_offset(iter: impl Iterator<Item = EulerSeg>, offset: f32, tol: f3
         let mut int0 = 0.0;
         let (n_frac, robust) = if k1.abs() < K1_THRESH {
             let k = k0 + 0.5 * k1;
-            let n_frac = (k * (k * dist_scaled + 1.0)).abs().sqrt();
+            let n_frac = ops32::sqrt((k * (k * dist_scaled + 1.0)).abs());
             (n_frac, EspcRobust::LowK1)
         } else if dist_scaled.abs() < DIST_THRESH {
-            let f = |x: f32| x * x.abs().sqrt();
+            let f = |x: f32| x * ops32::sqrt(x.abs());
             a = k1;
             b = k0;
             int0 = f(b);
@@ -69,7 +121,7 @@ pub fn flatten_offset(iter: impl Iterator<Item = EulerSeg>, offset: f32, tol: f3
             let int1 = espc_int_approx(a + b);
             integral = int1 - int0;
             let k_peak = k0 - k1 * b / a;
-            let integrand_peak = (k_peak * (k_peak * dist_scaled + 1.0)).abs().sqrt();
+            let integrand_peak = ops32::sqrt((k_peak * (k_peak * dist_scaled + 1.0)).abs());
             let scaled_int = integral * integrand_peak / a;
             let n_frac = scaled_int;
             (n_frac, EspcRobust::Normal)
@@ -81,7 +133,7 @@ pub fn flatten_offset(iter: impl Iterator<Item = EulerSeg>, offset: f32, tol: f3
                 EspcRobust::LowK1 => t,
                 // Note opportunities to minimize divergence
                 EspcRobust::LowDist => {
-                    let c = (integral * t + int0).cbrt();
+                    let c = ops32::cbrt(integral * t + int0);
                     let inv = c * c.abs();
                     (inv - b) / a
                 }
@@ -90,10 +142,11 @@ pub fn flatten_offset(iter: impl Iterator<Item = EulerSeg>, offset: f32, tol: f3
                     (inv - b) / a
                 }
             };
-            result.line_to(to_kurbo(es.eval_with_offset(s, offset)));
+            let next = es.eval_with_offset(s, offset);
+            sink.line(path_ix, prev, next);
+            prev = next;
         }
     }
-    result
 }
 
 const BREAK1: f32 = 0.8;
@@ -110,9 +163,9 @@ const QUAD_C2: f32 = 0.16145779359520596;
 pub fn espc_int_approx(x: f32) -> f32 {
     let y = x.abs();
     let a = if y < BREAK1 {
-        (SIN_SCALE * y).sin() * (1.0 / SIN_SCALE)
+        ops32::sin(SIN_SCALE * y) * (1.0 / SIN_SCALE)
     } else if y < BREAK2 {
-        (8.0f32.sqrt() / 3.0) * (y - 1.0) * (y - 1.0).abs().sqrt() + FRAC_PI_4
+        (ops32::sqrt(8.0) / 3.0) * (y - 1.0) * ops32::sqrt((y - 1.0).abs()) + FRAC_PI_4
     } else {
         let (a, b, c) = if y < BREAK3 {
             (QUAD_A1, QUAD_B1, QUAD_C1)
@@ -121,17 +174,17 @@ pub fn espc_int_approx(x: f32) -> f32 {
         };
         a * y * y + b * y + c
     };
-    a.copysign(x)
+    ops32::copysign(a, x)
 }
 
 pub fn espc_int_inv_approx(x: f32) -> f32 {
     let y = x.abs();
     let a = if y < 0.7010707591262915 {
-        (x * SIN_SCALE).asin() * (1.0 / SIN_SCALE)
+        ops32::asin(x * SIN_SCALE) * (1.0 / SIN_SCALE)
     } else if y < 0.903249293595206 {
         let b = y - FRAC_PI_4;
-        let u = b.abs().powf(2. / 3.).copysign(b);
-        u * (9.0f32 / 8.).cbrt() + 1.0
+        let u = ops32::copysign(ops32::powf(b.abs(), 2. / 3.), b);
+        u * ops32::cbrt(9.0 / 8.) + 1.0
     } else {
         let (u, v, w) = if y < 2.038857793595206 {
             const B: f32 = 0.5 * QUAD_B1 / QUAD_A1;
@@ -140,9 +193,9 @@ pub fn espc_int_inv_approx(x: f32) -> f32 {
             const B: f32 = 0.5 * QUAD_B2 / QUAD_A2;
             (B * B - QUAD_C2 / QUAD_A2, 1.0 / QUAD_A2, B)
         };
-        (u + v * y).sqrt() - w
+        ops32::sqrt(u + v * y) - w
     };
-    a.copysign(x)
+    ops32::copysign(a, x)
 }
 
 // The following two functions are for experimentation and do not need to
@@ -155,36 +208,36 @@ pub fn n_subdiv_approx(k0: f32, k1: f32, scale: f32, dist: f32, tol: f32) -> f32
     let b = -1.0 - 2.0 * dist_scaled * k0;
     let integral = espc_int_approx(a + b) - espc_int_approx(b);
     let k_peak = k0 - k1 * b / a;
-    let integrand_peak = (k_peak * (k_peak * dist_scaled + 1.0)).abs().sqrt();
+    let integrand_peak = ops32::sqrt((k_peak * (k_peak * dist_scaled + 1.0)).abs());
     let scaled_int = integral * integrand_peak / a;
-    0.5 * FRAC_1_SQRT_2 * (scale / tol).sqrt() * scaled_int
+    0.5 * FRAC_1_SQRT_2 * ops32::sqrt(scale / tol) * scaled_int
 }
 
 pub fn n_subdiv_robust(k0: f32, k1: f32, scale: f32, dist: f32, tol: f32) -> f32 {
     let dist_scaled = dist / scale;
     // The number of subdivisions for curvature = 1
-    let scale_multiplier = 0.5 * FRAC_1_SQRT_2 * (scale / tol).sqrt();
+    let scale_multiplier = 0.5 * FRAC_1_SQRT_2 * ops32::sqrt(scale / tol);
     // TODO: tune these thresholds
     const K1_THRESH: f32 = 1e-3;
     const DIST_THRESH: f32 = 1e-3;
     if k1.abs() < K1_THRESH {
         //println!("below k1 thresh");
         let k = k0 + 0.5 * k1;
-        return scale_multiplier * (k * (k * dist_scaled + 1.0)).abs().sqrt();
+        return scale_multiplier * ops32::sqrt((k * (k * dist_scaled + 1.0)).abs());
     }
     if dist.abs() < DIST_THRESH {
         // This is computed as a special case, which is easy to reason about,
         // but on GPU we might try to minimize divergence by unifying with the
         // main case.
         //println!("below dist thresh");
-        let f = |x: f32| x * x.abs().sqrt();
+        let f = |x: f32| x * ops32::sqrt(x.abs());
         return (2. / 3.) * scale_multiplier * (f(k0 + k1) - f(k0)) / k1;
     }
     let a = -2.0 * dist_scaled * k1;
     let b = -1.0 - 2.0 * dist_scaled * k0;
     let integral = espc_int_approx(a + b) - espc_int_approx(b);
     let k_peak = k0 - k1 * b / a;
-    let integrand_peak = (k_peak * (k_peak * dist_scaled + 1.0)).abs().sqrt();
+    let integrand_peak = ops32::sqrt((k_peak * (k_peak * dist_scaled + 1.0)).abs());
     let scaled_int = integral * integrand_peak / a;
     scale_multiplier * scaled_int
 }
@@ -267,4 +320,44 @@ mod test {
             assert!(err < expected_err as f64 * 1.2);
         }
     }
+
+    /// `flatten_offset` is now a thin `BezPathSink` wrapper over
+    /// `flatten_offset_to`; collecting the same flattening into a
+    /// `Vec<LineSoup>` instead should produce the exact same vertices (and
+    /// the `path_ix` tag passed through), not just a similar count.
+    #[test]
+    fn line_soup_matches_bez_path_output() {
+        let ep = crate::euler::EulerParams::from_angles(0.3, -0.2);
+        let es = EulerSeg {
+            p0: Point::new(0.0, 0.0),
+            p1: Point::new(1.0, 0.0),
+            params: EulerParams::from_f64(&ep),
+        };
+        let tol = 0.05;
+        let offset = 0.1;
+        let ref_path = flatten_offset([es].into_iter(), offset, tol);
+
+        let mut soup = Vec::new();
+        flatten_offset_to([es].into_iter(), offset, tol, 7, &mut soup);
+
+        let line_tos: Vec<kurbo::Point> = ref_path
+            .elements()
+            .iter()
+            .filter_map(|el| match el {
+                kurbo::PathEl::LineTo(p) => Some(*p),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(soup.len(), line_tos.len());
+
+        let mut prev = to_kurbo(es.eval_with_offset(0.0, offset));
+        for (seg, expected_end) in soup.iter().zip(&line_tos) {
+            assert_eq!(seg.path_ix, 7);
+            let p0 = kurbo::Point::new(seg.p0[0] as f64, seg.p0[1] as f64);
+            let p1 = kurbo::Point::new(seg.p1[0] as f64, seg.p1[1] as f64);
+            assert!((p0 - prev).hypot() < 1e-6);
+            assert!((p1 - *expected_end).hypot() < 1e-6);
+            prev = p1;
+        }
+    }
 }
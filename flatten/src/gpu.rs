@@ -0,0 +1,554 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! wgpu dispatch harness for the ESPC flattening compute kernel.
+//!
+//! This mirrors the CPU `flatten_offset` in `flatten.rs` closely enough that
+//! the two can be diffed by line count; see `perf_graph`'s `Gpu` primitive
+//! type and `diff_against_cpu` below.
+
+use bytemuck::{Pod, Zeroable};
+use kurbo::{CubicBez, Line, PathSeg};
+use vello::util::RenderContext;
+use wgpu::util::DeviceExt;
+
+use crate::euler::{CubicToEulerIter, EulerSeg};
+use crate::stroke::{Lowering, LoweredPath};
+use crate::svg::SvgScene;
+
+/// GPU-side representation of an `EulerSeg`, matching the `EulerSeg` struct
+/// in `shaders/flatten.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GpuEulerSeg {
+    p0: [f32; 2],
+    p1: [f32; 2],
+    th0: f32,
+    th1: f32,
+    k0: f32,
+    k1: f32,
+    ch: f32,
+    // pad to the WGSL struct's 40-byte array stride (36 bytes of fields
+    // rounded up to the 8-byte alignment of `vec2<f32>`).
+    _pad: f32,
+}
+
+/// GPU-side line segment, matching `LineSoup` in `shaders/flatten.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Debug)]
+pub struct LineSoup {
+    pub path_ix: u32,
+    // WGSL aligns `vec2<f32>` to 8 bytes, so `p0` starts at offset 8.
+    pub _pad: u32,
+    pub p0: [f32; 2],
+    pub p1: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Config {
+    offset: f32,
+    tol: f32,
+}
+
+/// GPU-side output line, matching `StrokeLine` in `shaders/flatten.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Debug)]
+pub struct StrokeLine {
+    pub kind: u32,
+    // WGSL aligns `vec2<f32>` to 8 bytes, so `p0` starts at offset 8.
+    pub _pad: u32,
+    pub p0: [f32; 2],
+    pub p1: [f32; 2],
+}
+
+impl GpuEulerSeg {
+    fn from_euler_seg(es: &EulerSeg) -> Self {
+        GpuEulerSeg {
+            p0: [es.p0.x as f32, es.p0.y as f32],
+            p1: [es.p1.x as f32, es.p1.y as f32],
+            th0: es.params.th0 as f32,
+            th1: es.params.th1 as f32,
+            k0: es.params.k0 as f32,
+            k1: es.params.k1 as f32,
+            ch: es.params.ch as f32,
+            _pad: 0.0,
+        }
+    }
+}
+
+/// Maximum number of output lines to reserve space for.
+///
+/// Each input segment can emit an unbounded number of lines in principle,
+/// but in practice this is plenty for the scenes this crate tests against.
+const MAX_LINES_PER_SEG: usize = 64;
+
+/// Dispatch the ESPC flattening kernel on `device`/`queue`, returning the
+/// emitted `LineSoup` entries.
+///
+/// This reuses a `wgpu::Device`/`Queue` pair, such as the ones vended by
+/// vello's `RenderContext` in the demo binary, rather than creating its own.
+pub async fn flatten_on_gpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    segs: &[EulerSeg],
+    offset: f64,
+    tol: f64,
+) -> Vec<LineSoup> {
+    let gpu_segs: Vec<GpuEulerSeg> = segs.iter().map(GpuEulerSeg::from_euler_seg).collect();
+    let max_lines = segs.len() * MAX_LINES_PER_SEG;
+
+    let seg_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("espc segments"),
+        contents: bytemuck::cast_slice(&gpu_segs),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    let line_buf = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("espc lines"),
+        size: (max_lines * std::mem::size_of::<LineSoup>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let count_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("espc line count"),
+        contents: bytemuck::cast_slice(&[0u32]),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+    });
+    let config = Config {
+        offset: offset as f32,
+        tol: tol as f32,
+    };
+    let config_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("espc config"),
+        contents: bytemuck::bytes_of(&config),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("flatten.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shaders/flatten.wgsl").into()),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("flatten_kernel"),
+        layout: None,
+        module: &shader,
+        entry_point: "flatten_kernel",
+        compilation_options: Default::default(),
+        cache: None,
+    });
+    let bind_group_layout = pipeline.get_bind_group_layout(0);
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("flatten_kernel bind group"),
+        layout: &bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: seg_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: line_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: count_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: config_buf.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("flatten_kernel encoder"),
+    });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("flatten_kernel pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        let n_wg = (segs.len() as u32).div_ceil(256).max(1);
+        pass.dispatch_workgroups(n_wg, 1, 1);
+    }
+    let readback_lines = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("espc lines readback"),
+        size: line_buf.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let readback_count = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("espc count readback"),
+        size: count_buf.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_buffer_to_buffer(&line_buf, 0, &readback_lines, 0, line_buf.size());
+    encoder.copy_buffer_to_buffer(&count_buf, 0, &readback_count, 0, count_buf.size());
+    queue.submit(Some(encoder.finish()));
+
+    let count_slice = readback_count.slice(..);
+    count_slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+    let n_lines = bytemuck::cast_slice::<u8, u32>(&count_slice.get_mapped_range())[0] as usize;
+
+    let line_slice = readback_lines.slice(..);
+    line_slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+    let lines: &[LineSoup] = bytemuck::cast_slice(&line_slice.get_mapped_range());
+    lines[..n_lines.min(lines.len())].to_vec()
+}
+
+/// Run the GPU flattening kernel and compare its output against the CPU
+/// `flatten_offset` path, printing a summary.
+///
+/// Returns `true` if the GPU and CPU line counts differ by at most one line
+/// per segment. Line positions are not compared.
+pub async fn diff_against_cpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    segs: &[EulerSeg],
+    offset: f64,
+    tol: f64,
+) -> bool {
+    let gpu_lines = flatten_on_gpu(device, queue, segs, offset, tol).await;
+    let mut cpu_n = 0usize;
+    for es in segs {
+        let mut n = 0usize;
+        crate::flatten::flatten_offset(es, offset, tol, |_| n += 1);
+        cpu_n += n;
+    }
+    let ok = gpu_lines.len().abs_diff(cpu_n) <= segs.len().max(1);
+    println!(
+        "gpu flatten: {} lines, cpu flatten: {cpu_n} lines, match={ok}",
+        gpu_lines.len()
+    );
+    ok
+}
+
+/// Dispatch the `do_euler_seg` port (cusp detection plus ESPC/evolute
+/// stitching) on `device`/`queue`, returning the emitted `StrokeLine`
+/// entries, one invocation per `EulerSeg`.
+///
+/// This is a two-phase dispatch: a `stroke_count_kernel` pass sizes each
+/// segment's output, the exclusive prefix sum over those counts is computed
+/// here on the host (the simplest correct scan given the modest segment
+/// counts this crate deals with; a GPU parallel scan would be the next step
+/// for very large inputs), and a `stroke_write_kernel` pass places every
+/// line at its precomputed, deterministic offset.
+pub async fn stroke_expand_on_gpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    segs: &[EulerSeg],
+    offset: f64,
+    tol: f64,
+) -> Vec<StrokeLine> {
+    if segs.is_empty() {
+        return vec![];
+    }
+    let gpu_segs: Vec<GpuEulerSeg> = segs.iter().map(GpuEulerSeg::from_euler_seg).collect();
+    let config = Config {
+        offset: offset as f32,
+        tol: tol as f32,
+    };
+
+    let seg_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("stroke expand segments"),
+        contents: bytemuck::cast_slice(&gpu_segs),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    let config_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("stroke expand config"),
+        contents: bytemuck::bytes_of(&config),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    let counts_buf = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("stroke expand counts"),
+        size: (gpu_segs.len() * std::mem::size_of::<u32>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("flatten.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shaders/flatten.wgsl").into()),
+    });
+
+    // Phase 1: count. `wgpu` derives each pipeline's bind group layout from
+    // only the bindings its entry point actually references, so this group
+    // covers just `segments` (0), `seg_counts` (4), and `stroke_config` (7).
+    let count_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("stroke_count_kernel"),
+        layout: None,
+        module: &shader,
+        entry_point: "stroke_count_kernel",
+        compilation_options: Default::default(),
+        cache: None,
+    });
+    let count_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("stroke_count_kernel bind group"),
+        layout: &count_pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: seg_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: counts_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: config_buf.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("stroke_count_kernel encoder"),
+    });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("stroke_count_kernel pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&count_pipeline);
+        pass.set_bind_group(0, &count_bind_group, &[]);
+        let n_wg = (gpu_segs.len() as u32).div_ceil(256).max(1);
+        pass.dispatch_workgroups(n_wg, 1, 1);
+    }
+    let counts_readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("stroke expand counts readback"),
+        size: counts_buf.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_buffer_to_buffer(&counts_buf, 0, &counts_readback, 0, counts_buf.size());
+    queue.submit(Some(encoder.finish()));
+    let counts_slice = counts_readback.slice(..);
+    counts_slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+    let counts: Vec<u32> =
+        bytemuck::cast_slice::<u8, u32>(&counts_slice.get_mapped_range()).to_vec();
+
+    // Host-side exclusive prefix sum, so the write pass can place every
+    // segment's lines at a deterministic, non-overlapping offset.
+    let mut offsets = Vec::with_capacity(counts.len());
+    let mut total = 0u32;
+    for &n in &counts {
+        offsets.push(total);
+        total += n;
+    }
+    if total == 0 {
+        return vec![];
+    }
+
+    // Phase 2: write, now that every segment knows its output offset.
+    let offsets_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("stroke expand offsets"),
+        contents: bytemuck::cast_slice(&offsets),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    let lines_buf = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("stroke expand lines"),
+        size: (total as usize * std::mem::size_of::<StrokeLine>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let write_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("stroke_write_kernel"),
+        layout: None,
+        module: &shader,
+        entry_point: "stroke_write_kernel",
+        compilation_options: Default::default(),
+        cache: None,
+    });
+    let write_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("stroke_write_kernel bind group"),
+        layout: &write_pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: seg_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: offsets_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: lines_buf.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: config_buf.as_entire_binding(),
+            },
+        ],
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("stroke_write_kernel encoder"),
+    });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("stroke_write_kernel pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&write_pipeline);
+        pass.set_bind_group(0, &write_bind_group, &[]);
+        let n_wg = (gpu_segs.len() as u32).div_ceil(256).max(1);
+        pass.dispatch_workgroups(n_wg, 1, 1);
+    }
+    let lines_readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("stroke expand lines readback"),
+        size: lines_buf.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_buffer_to_buffer(&lines_buf, 0, &lines_readback, 0, lines_buf.size());
+    queue.submit(Some(encoder.finish()));
+    let lines_slice = lines_readback.slice(..);
+    lines_slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+    bytemuck::cast_slice::<u8, StrokeLine>(&lines_slice.get_mapped_range()).to_vec()
+}
+
+/// Count the lines `StrokeContour::do_euler_seg` would emit for `segs` on
+/// the CPU, using the exact (non-approximated) `Lowering` primitives as a
+/// reference oracle for `stroke_expand_on_gpu`.
+fn cpu_stroke_expand_line_count(segs: &[EulerSeg], offset: f64, tol: f64) -> usize {
+    let mut n = 0;
+    for es in segs {
+        let chord_len = (es.p1 - es.p0).hypot();
+        let cusp0 = es.params.curvature(0.) * offset + chord_len;
+        let cusp1 = es.params.curvature(1.) * offset + chord_len;
+        let t = if cusp0 * cusp1 >= 0.0 {
+            1.0
+        } else {
+            cusp0 / (cusp0 - cusp1)
+        };
+        let mut path: LoweredPath<Line> = LoweredPath::default();
+        path.move_to(es.p0);
+        if cusp0 >= 0.0 {
+            Line::lower_espc(es, &mut path, 0.0..t, offset, tol);
+            if t < 1.0 {
+                Line::lower_es_evolute(es, &mut path, t..1.0, tol);
+                Line::lower_espc(es, &mut path, t..1.0, offset, tol);
+            }
+        } else {
+            Line::lower_es_evolute(es, &mut path, 0.0..t, tol);
+            Line::lower_espc(es, &mut path, 0.0..t, offset, tol);
+            if t < 1.0 {
+                Line::lower_espc(es, &mut path, t..1.0, offset, tol);
+            }
+        }
+        n += path.path.len();
+    }
+    n
+}
+
+/// Run the `do_euler_seg` GPU port and compare its line count against the
+/// CPU reference oracle, printing a summary.
+///
+/// Returns `true` if the two line counts agree within a per-segment slack
+/// (the same style of check `diff_against_cpu` uses for `flatten_kernel`).
+pub async fn diff_stroke_expand_against_cpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    segs: &[EulerSeg],
+    offset: f64,
+    tol: f64,
+) -> bool {
+    let gpu_lines = stroke_expand_on_gpu(device, queue, segs, offset, tol).await;
+    let cpu_n = cpu_stroke_expand_line_count(segs, offset, tol);
+    let ok = gpu_lines.len().abs_diff(cpu_n) <= 2 * segs.len().max(1);
+    println!(
+        "gpu stroke expand: {} lines, cpu stroke expand: {cpu_n} lines, match={ok}",
+        gpu_lines.len()
+    );
+    ok
+}
+
+/// A reusable wgpu device/queue pair, acquired the same way the `anims`
+/// demo binary acquires one from vello's `RenderContext`.
+pub struct GpuContext {
+    render_cx: RenderContext,
+    dev_id: usize,
+}
+
+impl GpuContext {
+    pub fn new() -> Self {
+        let mut render_cx = RenderContext::new();
+        let dev_id = pollster::block_on(render_cx.device(None))
+            .expect("no compatible wgpu device found");
+        GpuContext { render_cx, dev_id }
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        &self.render_cx.devices[self.dev_id].device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.render_cx.devices[self.dev_id].queue
+    }
+}
+
+impl Default for GpuContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run the ESPC flattening kernel over every path in `scene`, at the given
+/// `tolerance`, and return the total number of emitted lines.
+///
+/// This mirrors `SvgScene::expand`'s CPU counting in `perf_graph`, but
+/// dispatches the actual WGSL kernel via `cx` instead of the CPU flattener.
+pub fn expand_gpu(scene: &SvgScene, tolerance: f64, cx: &GpuContext) -> usize {
+    let mut n_segs = 0;
+    for styled in &scene.paths {
+        let local_tol = tolerance / styled.scale();
+        let segs: Vec<EulerSeg> = styled
+            .path
+            .segments()
+            .filter_map(|seg| match seg {
+                PathSeg::Cubic(c) => Some(c),
+                _ => None,
+            })
+            .flat_map(|c| CubicToEulerIter::new(c, local_tol))
+            .collect();
+        let offset = 0.5 * styled.style.width;
+        let lines = pollster::block_on(flatten_on_gpu(
+            cx.device(),
+            cx.queue(),
+            &segs,
+            offset,
+            local_tol,
+        ));
+        n_segs += lines.len();
+    }
+    n_segs
+}
+
+/// CLI entry point: fit a sample cubic to Euler segments, flatten both on
+/// GPU and CPU, and report whether they agree.
+///
+/// This reuses vello's `RenderContext`, the same device-acquisition path the
+/// `anims` demo binary uses, rather than standing up its own wgpu instance.
+pub fn gpu_main() {
+    let c = CubicBez::new((0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0));
+    let segs: Vec<EulerSeg> = CubicToEulerIter::new(c, 0.01).collect();
+
+    pollster::block_on(async {
+        let mut render_cx = RenderContext::new();
+        let dev_id = render_cx
+            .device(None)
+            .await
+            .expect("no compatible wgpu device found");
+        let device = &render_cx.devices[dev_id].device;
+        let queue = &render_cx.devices[dev_id].queue;
+        diff_against_cpu(device, queue, &segs, 10.0, 0.1).await;
+        diff_stroke_expand_against_cpu(device, queue, &segs, 10.0, 0.1).await;
+    });
+}
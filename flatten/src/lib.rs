@@ -1,17 +1,27 @@
 mod arc_segment;
+pub mod clip;
 pub mod cubic32;
 pub mod cubic_err_plot;
+pub mod dilate;
 pub mod euler;
 pub mod euler32;
 pub mod euler_arc;
 pub mod evolute;
 pub mod flatten;
 pub mod flatten32;
+#[cfg(feature = "wgpu")]
+pub mod gpu;
+#[cfg(feature = "lyon")]
+pub mod lyon;
+mod ops32;
 pub mod perf_graph;
+pub mod raster;
 #[cfg(feature = "skia-safe")]
 pub mod skia;
+pub mod spiro;
 pub mod stroke;
 pub mod svg;
 pub mod to_rvg;
 
 pub use arc_segment::ArcSegment;
+pub use stroke::{AaTri, FillRule, QuadSegment, SvgExportOpts, Vertex};
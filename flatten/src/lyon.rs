@@ -0,0 +1,75 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Testbed for evaluating lyon's stroke tessellator, as a second widely-used
+//! CPU-stroker baseline alongside `skia` (see `perf_graph::PrimType::Lyon`).
+
+use std::time::{Duration, Instant};
+
+use kurbo::{BezPath, Cap, Join, PathEl, Stroke};
+use lyon_tessellation::{
+    geom::point, BuffersBuilder, LineCap, LineJoin, StrokeOptions, StrokeTessellator,
+    VertexBuffers,
+};
+
+fn kurbo_to_lyon(path: &BezPath) -> lyon_tessellation::path::Path {
+    let mut builder = lyon_tessellation::path::Path::builder();
+    for el in path.elements() {
+        match el {
+            PathEl::MoveTo(p) => _ = builder.begin(point(p.x as f32, p.y as f32)),
+            PathEl::LineTo(p) => _ = builder.line_to(point(p.x as f32, p.y as f32)),
+            PathEl::QuadTo(p1, p2) => _ = builder.quadratic_bezier_to(
+                point(p1.x as f32, p1.y as f32),
+                point(p2.x as f32, p2.y as f32),
+            ),
+            PathEl::CurveTo(p1, p2, p3) => _ = builder.cubic_bezier_to(
+                point(p1.x as f32, p1.y as f32),
+                point(p2.x as f32, p2.y as f32),
+                point(p3.x as f32, p3.y as f32),
+            ),
+            PathEl::ClosePath => builder.end(true),
+        }
+    }
+    builder.build()
+}
+
+fn lyon_cap(cap: Cap) -> LineCap {
+    match cap {
+        Cap::Butt => LineCap::Butt,
+        Cap::Round => LineCap::Round,
+        Cap::Square => LineCap::Square,
+    }
+}
+
+fn lyon_join(join: Join) -> LineJoin {
+    match join {
+        Join::Bevel => LineJoin::Bevel,
+        Join::Miter => LineJoin::Miter,
+        Join::Round => LineJoin::Round,
+    }
+}
+
+/// Stroke `path` with lyon's `StrokeTessellator`, returning the number of
+/// emitted vertices and the tessellation wall-clock time.
+pub fn stroke_expand(path: &BezPath, style: &Stroke, tolerance: f64) -> (usize, Duration) {
+    let lyon_path = kurbo_to_lyon(path);
+    let options = StrokeOptions::default()
+        .with_line_width(style.width as f32)
+        .with_tolerance(tolerance as f32)
+        .with_start_cap(lyon_cap(style.start_cap))
+        .with_end_cap(lyon_cap(style.end_cap))
+        .with_line_join(lyon_join(style.join))
+        .with_miter_limit(style.miter_limit as f32);
+    let mut buffers: VertexBuffers<(), u32> = VertexBuffers::new();
+    let mut tessellator = StrokeTessellator::new();
+    let start = Instant::now();
+    tessellator
+        .tessellate_path(
+            &lyon_path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |_: lyon_tessellation::StrokeVertex| ()),
+        )
+        .unwrap();
+    let elapsed = start.elapsed();
+    (buffers.vertices.len(), elapsed)
+}
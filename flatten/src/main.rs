@@ -17,7 +17,10 @@ enum Args {
     Evolute,
     Espc,
     EstFlattenErr,
+    #[cfg(feature = "wgpu")]
+    Gpu,
     PrimCountGraph(flatten::perf_graph::PrimCountArgs),
+    Raster(flatten::raster::RasterArgs),
     Stroke,
     Svg(flatten::svg::SvgArgs),
     ToRvg(flatten::to_rvg::ToRvgArgs),
@@ -90,12 +93,24 @@ fn main() {
             let iter = CubicToEulerIter::new(c, 0.1);
             let path = flatten_offset(iter, 0.0, 0.1);
             println!("{}", path.to_svg());
+
+            // Exercise the cubic's own parabola-integral flattening directly
+            // (as opposed to going through the Euler-spiral fit above).
+            let mut direct = kurbo::BezPath::new();
+            direct.move_to(kurbo::Point::new(c.p0.x as f64, c.p0.y as f64));
+            c.flatten(0.1, &mut |p| {
+                direct.line_to(kurbo::Point::new(p.x as f64, p.y as f64));
+            });
+            println!("{}", direct.to_svg());
         }
         Args::CubicErr(ce) => flatten::cubic_err_plot::cubic_err_plot(ce),
         Args::Espc => main_espc(),
         Args::EstFlattenErr => main_est_flatten_err(),
         Args::Evolute => flatten::evolute::euler_evolute_main(),
+        #[cfg(feature = "wgpu")]
+        Args::Gpu => flatten::gpu::gpu_main(),
         Args::PrimCountGraph(args) => flatten::perf_graph::perf_graph(args),
+        Args::Raster(args) => flatten::raster::raster_main(args),
         Args::Stroke => flatten::stroke::stroke_main(),
         Args::Svg(args) => flatten::svg::svg_main(args),
         Args::ToRvg(args) => flatten::to_rvg::to_rvg_main(args),
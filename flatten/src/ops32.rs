@@ -0,0 +1,141 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deterministic f32 transcendental functions for the GPU-matching reference
+//! path (`flatten32`/`cubic32`/`euler32`).
+//!
+//! `f32::sin`, `cos`, `cbrt`, `sqrt`, `powf`, `asin`, `atan2`, and `hypot` are
+//! platform- and compiler-dependent in their last bit or two, so a CPU reference built on
+//! top of them can silently disagree with the GPU shader (and with other
+//! hosts) by more than rounding. With the `libm` feature enabled, these
+//! route through `libm`'s software implementations instead, which give the
+//! same bits on every host; without it, they fall back to the std methods
+//! so the crate still builds without the extra dependency.
+
+#[cfg(feature = "libm")]
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(feature = "libm")]
+pub fn asin(x: f32) -> f32 {
+    libm::asinf(x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn asin(x: f32) -> f32 {
+    x.asin()
+}
+
+#[cfg(feature = "libm")]
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(feature = "libm")]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    libm::atan2f(y, x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+#[cfg(feature = "libm")]
+pub fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(feature = "libm")]
+pub fn cbrt(x: f32) -> f32 {
+    libm::cbrtf(x)
+}
+#[cfg(not(feature = "libm"))]
+pub fn cbrt(x: f32) -> f32 {
+    x.cbrt()
+}
+
+#[cfg(feature = "libm")]
+pub fn powf(x: f32, y: f32) -> f32 {
+    libm::powf(x, y)
+}
+#[cfg(not(feature = "libm"))]
+pub fn powf(x: f32, y: f32) -> f32 {
+    x.powf(y)
+}
+
+#[cfg(feature = "libm")]
+pub fn hypot(x: f32, y: f32) -> f32 {
+    libm::hypotf(x, y)
+}
+#[cfg(not(feature = "libm"))]
+pub fn hypot(x: f32, y: f32) -> f32 {
+    x.hypot(y)
+}
+
+/// `powi` is exact integer repeated squaring on every platform already
+/// (no `libm` intrinsic exists for it), so this just gives call sites one
+/// place to route all their f32 math through.
+pub fn powi(x: f32, n: i32) -> f32 {
+    x.powi(n)
+}
+
+/// Sign transplant is exact bit manipulation on every platform already, so
+/// there's no std/libm divergence to guard against here; this just gives
+/// call sites one place to route all their f32 math through.
+pub fn copysign(x: f32, y: f32) -> f32 {
+    x.copysign(y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whichever path `feature = "libm"` routes these through (software libm
+    /// vs. std), they need to still agree with the true math to f32
+    /// precision; this would catch a transposed arg or wrong libm function
+    /// (e.g. `hypotf`'s arguments swapped) regardless of which path is
+    /// compiled in. It isn't a test of libm-vs-std bit-reproducibility
+    /// itself (that needs building both feature configs and diffing), but
+    /// it's the cheapest check that can run in a single `cargo test`.
+    #[test]
+    fn transcendentals_match_f64_reference() {
+        const EPS: f32 = 1e-5;
+        for i in -10..=10 {
+            let x = i as f32 * 0.3;
+            assert!((sin(x) - (x as f64).sin() as f32).abs() < EPS);
+            assert!((cos(x) - (x as f64).cos() as f32).abs() < EPS);
+        }
+        for i in -9..=9 {
+            let x = i as f32 * 0.1;
+            assert!((asin(x) - (x as f64).asin() as f32).abs() < EPS);
+        }
+        for i in 1..=10 {
+            let x = i as f32 * 0.5;
+            assert!((sqrt(x) - (x as f64).sqrt() as f32).abs() < EPS);
+            assert!((cbrt(x) - (x as f64).cbrt() as f32).abs() < EPS);
+            assert!((powf(x, 1.7) - (x as f64).powf(1.7) as f32).abs() < EPS);
+        }
+        for i in -5..=5 {
+            for j in -5..=5 {
+                if i == 0 && j == 0 {
+                    continue;
+                }
+                let (y, x) = (i as f32, j as f32);
+                assert!((atan2(y, x) - (y as f64).atan2(x as f64) as f32).abs() < EPS);
+                assert!((hypot(y, x) - (y as f64).hypot(x as f64) as f32).abs() < EPS);
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ use std::time::{Duration, Instant};
 use clap::Parser;
 use kurbo::Line;
 
-use crate::{arc_segment::ArcSegment, stroke::LoweredPath, svg::SvgScene};
+use crate::{arc_segment::ArcSegment, stroke::LoweredPath, stroke::QuadSegment, svg::SvgScene};
 
 #[derive(Parser)]
 pub struct PrimCountArgs {
@@ -20,9 +20,14 @@ pub struct PrimCountArgs {
 enum PrimType {
     Line,
     Arc,
+    Quad,
     // Not really a primitive type, but useful for comparison
     #[cfg(feature = "skia-safe")]
     Skia,
+    #[cfg(feature = "lyon")]
+    Lyon,
+    #[cfg(feature = "wgpu")]
+    Gpu,
 }
 
 #[derive(PartialEq)]
@@ -69,11 +74,18 @@ pub fn perf_graph(args: PrimCountArgs) {
         }
         println!();
     }
+    #[cfg(feature = "wgpu")]
+    let gpu_cx = crate::gpu::GpuContext::new();
     for prim_type in [
         PrimType::Line,
         PrimType::Arc,
+        PrimType::Quad,
         #[cfg(feature = "skia-safe")]
         PrimType::Skia,
+        #[cfg(feature = "lyon")]
+        PrimType::Lyon,
+        #[cfg(feature = "wgpu")]
+        PrimType::Gpu,
     ] {
         match graph_type {
             GraphType::Sum => println!("\"{:?}\"", prim_type),
@@ -105,18 +117,40 @@ pub fn perf_graph(args: PrimCountArgs) {
                             n_segs += paths.iter().map(|p| p.path.len()).sum::<usize>();
                             duration += start.elapsed();
                         }
+                        PrimType::Quad => {
+                            let paths: Vec<LoweredPath<QuadSegment>> = scene.expand(*tolerance);
+                            n_segs += paths.iter().map(|p| p.path.len()).sum::<usize>();
+                            duration += start.elapsed();
+                        }
                         #[cfg(feature = "skia-safe")]
                         PrimType::Skia => {
                             for path in &scene.paths {
                                 let (p, elapsed) = crate::skia::stroke_expand(
                                     &path.path,
                                     &path.style,
-                                    tolerance / path.scale,
+                                    tolerance / path.scale(),
                                 );
                                 duration += elapsed;
                                 n_segs += p.segments().count();
                             }
                         }
+                        #[cfg(feature = "lyon")]
+                        PrimType::Lyon => {
+                            for path in &scene.paths {
+                                let (n_verts, elapsed) = crate::lyon::stroke_expand(
+                                    &path.path,
+                                    &path.style,
+                                    tolerance / path.scale(),
+                                );
+                                duration += elapsed;
+                                n_segs += n_verts;
+                            }
+                        }
+                        #[cfg(feature = "wgpu")]
+                        PrimType::Gpu => {
+                            n_segs += crate::gpu::expand_gpu(scene, *tolerance, &gpu_cx);
+                            duration += start.elapsed();
+                        }
                     }
                 }
                 if args.chart_type == "timing" {
@@ -134,4 +168,22 @@ pub fn perf_graph(args: PrimCountArgs) {
             println!();
         }
     }
+    if args.chart_type == "count" {
+        // Surface the extra segment cost dashing adds, using the Line
+        // primitive as a representative baseline, at each tolerance.
+        println!("\"DashExtra\"");
+        for tolerance in tolerances {
+            let mut dashed = 0;
+            let mut undashed = 0;
+            for scene in &scenes {
+                let paths: Vec<LoweredPath<Line>> = scene.expand(*tolerance);
+                dashed += paths.iter().map(|p| p.path.len()).sum::<usize>();
+                let paths: Vec<LoweredPath<Line>> = scene.expand_undashed(*tolerance);
+                undashed += paths.iter().map(|p| p.path.len()).sum::<usize>();
+            }
+            println!("{tolerance} {dashed} {undashed}");
+        }
+        println!();
+        println!();
+    }
 }
@@ -0,0 +1,193 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A headless CPU scanline rasterizer for turning flattened line soup into
+//! pixels, so stroke-expansion output can be checked against reference
+//! images without a GPU.
+//!
+//! Uses the classic signed-area/cover accumulation scheme (as in FreeType's
+//! smooth rasterizer and `font-rs`): each line segment deposits a signed
+//! area contribution into the pixel(s) it crosses, and a running prefix sum
+//! along each row turns that into final coverage.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use clap::Parser;
+use kurbo::{Line, Point};
+
+use crate::stroke::stroke_undashed;
+use crate::svg::SvgScene;
+
+pub struct Raster {
+    w: usize,
+    h: usize,
+    // One extra column so a line ending exactly at the right edge still
+    // has a slot for its area contribution.
+    a: Vec<f32>,
+}
+
+impl Raster {
+    pub fn new(w: usize, h: usize) -> Self {
+        Raster {
+            w,
+            h,
+            a: vec![0.0; (w + 1) * h],
+        }
+    }
+
+    /// Accumulate the signed-area contribution of the line from `p0` to `p1`.
+    ///
+    /// Lines are signed by their vertical direction, so a closed contour's
+    /// winding number falls out of the prefix sum done in `to_alpha`.
+    pub fn draw_line(&mut self, p0: Point, p1: Point) {
+        if p0.y == p1.y {
+            return;
+        }
+        let (dir, p0, p1) = if p0.y < p1.y {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+        let y0 = p0.y.max(0.0).min(self.h as f64);
+        let y1 = p1.y.max(0.0).min(self.h as f64);
+        if y0 >= y1 {
+            return;
+        }
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let mut y = y0;
+        let mut x = p0.x + dxdy * (y0 - p0.y);
+        let mut iy = y.floor() as usize;
+        while y < y1 && iy < self.h {
+            let row = iy * (self.w + 1);
+            let dy = ((iy + 1) as f64).min(y1) - y;
+            let xnext = x + dxdy * dy;
+            self.draw_row_span(row, x, xnext, dy * dir);
+            y += dy;
+            x = xnext;
+            iy += 1;
+        }
+    }
+
+    /// Deposit the signed area for a sub-segment that stays within a single
+    /// scanline row, spanning from `x0` to `x1` horizontally with total
+    /// signed vertical extent `d`.
+    fn draw_row_span(&mut self, row: usize, x0: f64, x1: f64, d: f64) {
+        let (lo_x, hi_x) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
+        let lo_x = lo_x.max(0.0);
+        let hi_x = hi_x.min(self.w as f64);
+        if hi_x <= lo_x {
+            // Degenerate (vertical in x) or fully off-canvas: deposit all of
+            // `d` at whichever edge column the span collapsed to.
+            let xi = x0.clamp(0.0, self.w as f64) as usize;
+            self.a[row + xi] += d as f32;
+            return;
+        }
+        let dx = hi_x - lo_x;
+        let first = lo_x.floor() as usize;
+        let last = hi_x.ceil() as usize;
+        for xi in first..last {
+            let lo = lo_x.max(xi as f64);
+            let hi = hi_x.min((xi + 1) as f64);
+            if hi <= lo {
+                continue;
+            }
+            let d_local = d * (hi - lo) / dx;
+            let frac = 0.5 * ((lo - xi as f64) + (hi - xi as f64));
+            self.a[row + xi] += (d_local * (1.0 - frac)) as f32;
+            self.a[row + xi + 1] += (d_local * frac) as f32;
+        }
+    }
+
+    /// Run the per-row prefix sum and return an 8-bit coverage buffer,
+    /// `width * height` bytes, row-major, nonzero winding.
+    pub fn to_alpha(&self) -> Vec<u8> {
+        let mut result = vec![0u8; self.w * self.h];
+        for y in 0..self.h {
+            let mut acc = 0.0f32;
+            let row = &self.a[y * (self.w + 1)..y * (self.w + 1) + self.w + 1];
+            for x in 0..self.w {
+                acc += row[x];
+                let coverage = acc.abs().min(1.0);
+                result[y * self.w + x] = (coverage * 255.0 + 0.5) as u8;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rasterize an axis-aligned square whose edges land on exact pixel
+    /// boundaries, so anti-aliasing doesn't muddy the expectation: interior
+    /// pixels should be fully covered, exterior pixels uncovered, and the
+    /// total coverage should equal the square's analytic area exactly. This
+    /// exercises the cover/area accumulation in `draw_line`/`draw_row_span`
+    /// and the prefix sum in `to_alpha` end to end.
+    #[test]
+    fn square_area_and_coverage() {
+        let (w, h) = (8, 8);
+        let mut raster = Raster::new(w, h);
+        let pts = [
+            Point::new(2.0, 2.0),
+            Point::new(6.0, 2.0),
+            Point::new(6.0, 6.0),
+            Point::new(2.0, 6.0),
+            Point::new(2.0, 2.0),
+        ];
+        for seg in pts.windows(2) {
+            raster.draw_line(seg[0], seg[1]);
+        }
+        let alpha = raster.to_alpha();
+        assert_eq!(alpha[4 * w + 4], 255, "interior pixel should be fully covered");
+        assert_eq!(alpha[0 * w + 0], 0, "exterior pixel should be uncovered");
+        assert_eq!(alpha[2 * w + 2], 255, "top-left interior corner");
+        assert_eq!(alpha[5 * w + 5], 255, "bottom-right interior corner (square is half-open)");
+        assert_eq!(alpha[2 * w + 6], 0, "right edge column is outside the half-open square");
+        let total: u32 = alpha.iter().map(|&a| a as u32).sum();
+        assert_eq!(total, 4 * 4 * 255, "total coverage should equal the 4x4 analytic area");
+    }
+}
+
+/// Write an 8-bit grayscale alpha buffer as a PNG file.
+pub fn write_png(path: impl AsRef<Path>, width: usize, height: usize, alpha: &[u8]) {
+    let file = File::create(path).unwrap();
+    let w = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(alpha).unwrap();
+}
+
+#[derive(Parser)]
+pub struct RasterArgs {
+    filename: String,
+    #[arg(long, default_value = "1024")]
+    width: usize,
+    #[arg(long, default_value = "1024")]
+    height: usize,
+    #[arg(long, default_value = "out.png")]
+    out: String,
+}
+
+/// Load an SVG, stroke-expand every path to lines, and rasterize the result
+/// into a PNG for reference-image comparisons.
+pub fn raster_main(args: RasterArgs) {
+    let xml_str = std::fs::read_to_string(&args.filename).unwrap();
+    let scene = SvgScene::load(&xml_str).unwrap();
+    let mut raster = Raster::new(args.width, args.height);
+    let tolerance = 0.1;
+    for styled in &scene.paths {
+        let lowered = stroke_undashed::<Line>(&styled.path, &styled.style, tolerance / styled.scale())
+            .transform(styled.transform);
+        for line in &lowered.path {
+            raster.draw_line(line.p0, line.p1);
+        }
+    }
+    let alpha = raster.to_alpha();
+    write_png(&args.out, args.width, args.height, &alpha);
+}
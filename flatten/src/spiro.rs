@@ -0,0 +1,175 @@
+// Copyright 2024 the Vello Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Spiro-style G2 interpolating-spline fitter.
+//!
+//! Fits a smooth Euler-spiral spline through an ordered list of points by
+//! solving for each interior knot's tangent angle so that curvature matches
+//! across knot boundaries, then lowers the result into the crate's existing
+//! `LoweredPath<ArcSegment>` representation via `EulerSeg`.
+
+use std::f64::consts::PI;
+
+use kurbo::Point;
+
+use crate::arc_segment::ArcSegment;
+use crate::euler::{EulerParams, EulerSeg};
+use crate::stroke::{Lowering, LoweredPath};
+
+/// Sweeps of the knot-angle relaxation before giving up.
+const MAX_SWEEPS: usize = 20;
+/// Newton iterations per knot, per sweep.
+const MAX_NEWTON: usize = 8;
+/// Perturbation used for the finite-difference derivative of the per-knot
+/// curvature residual.
+const JAC_EPS: f64 = 1e-6;
+/// A Newton step is clamped to this many radians so a bad initial guess
+/// can't jump into an unrelated branch of `EulerParams::from_angles`' own
+/// (also Newton-based) solve.
+const MAX_STEP: f64 = 0.5;
+/// Residual (difference in physical curvature, in units of 1/length) below
+/// which a knot is considered converged.
+const CURVATURE_TOL: f64 = 1e-9;
+/// Chords shorter than this are treated as coincident points and skipped.
+const MIN_CHORD: f64 = 1e-9;
+
+/// Wrap an angle into `(-pi, pi]`.
+fn wrap_angle(th: f64) -> f64 {
+    let th = th % (2.0 * PI);
+    if th > PI {
+        th - 2.0 * PI
+    } else if th <= -PI {
+        th + 2.0 * PI
+    } else {
+        th
+    }
+}
+
+struct Span {
+    chord_len: f64,
+    chord_angle: f64,
+}
+
+/// Physical (not chord-normalized) curvature at the start/end of a span
+/// fit by `EulerParams::from_angles(th0, th1)`.
+fn boundary_curvatures(th0: f64, th1: f64, chord_len: f64) -> (f64, f64) {
+    let params = EulerParams::from_angles(th0, th1);
+    (params.curvature(0.0) / chord_len, params.curvature(1.0) / chord_len)
+}
+
+/// Solve for knot angle `angles[k]` by 1-D Newton iteration, holding every
+/// other knot angle fixed (a Gauss-Seidel sweep over the banded system).
+///
+/// `prev` / `next` are the spans on either side of knot `k`; `angles[k]` is
+/// the departure angle of `next` and the arrival angle of `prev`.
+fn relax_knot(angles: &mut [f64], k: usize, prev_k: usize, next_k: usize, prev: &Span, next: &Span) -> f64 {
+    let residual = |theta: f64| {
+        let th1_prev = wrap_angle(prev.chord_angle - theta);
+        let th0_prev = wrap_angle(angles[prev_k] - prev.chord_angle);
+        let (_, k_end) = boundary_curvatures(th0_prev, th1_prev, prev.chord_len);
+
+        let th0_next = wrap_angle(theta - next.chord_angle);
+        let th1_next = wrap_angle(next.chord_angle - angles[next_k]);
+        let (k_start, _) = boundary_curvatures(th0_next, th1_next, next.chord_len);
+
+        k_end - k_start
+    };
+    let mut theta = angles[k];
+    let mut last_residual = residual(theta);
+    for _ in 0..MAX_NEWTON {
+        if last_residual.abs() < CURVATURE_TOL {
+            break;
+        }
+        let deriv = (residual(theta + JAC_EPS) - residual(theta - JAC_EPS)) / (2.0 * JAC_EPS);
+        if deriv.abs() < 1e-9 {
+            break;
+        }
+        let step = (last_residual / deriv).clamp(-MAX_STEP, MAX_STEP);
+        theta -= step;
+        last_residual = residual(theta);
+    }
+    angles[k] = theta;
+    last_residual
+}
+
+/// Fit a G2-continuous Euler-spiral spline through `points` and lower it
+/// into arcs.
+///
+/// `closed` joins the last point back to the first; `tolerance` bounds the
+/// flattening error of the output arcs (through `ArcSegment`'s own
+/// subdivision). The knot-angle solve does not use it: it stops once every
+/// curvature residual is below `CURVATURE_TOL`, or after `MAX_SWEEPS` sweeps.
+pub fn fit_spiro(points: &[Point], closed: bool, tolerance: f64) -> LoweredPath<ArcSegment> {
+    let n = points.len();
+    assert!(n >= 2, "fit_spiro needs at least two points");
+
+    let n_spans = if closed { n } else { n - 1 };
+    let spans: Vec<Span> = (0..n_spans)
+        .map(|i| {
+            let p0 = points[i];
+            let p1 = points[(i + 1) % n];
+            let d = p1 - p0;
+            Span {
+                chord_len: d.hypot(),
+                chord_angle: d.atan2(),
+            }
+        })
+        .collect();
+
+    let mut path = LoweredPath::default();
+    path.move_to(points[0]);
+
+    if spans.iter().any(|s| s.chord_len < MIN_CHORD) || n_spans == 1 {
+        // Degenerate input (coincident points, or just two points): fall
+        // back to straight spans rather than fitting a spline.
+        for i in 0..n_spans {
+            path.line_to(points[(i + 1) % n]);
+        }
+        return path;
+    }
+
+    // One tangent-angle unknown per knot with spans on both sides: interior
+    // knots for an open curve, every knot for a closed one. Endpoints of an
+    // open curve are pinned to the chord angle of their single adjacent
+    // span (a clamped end tangent, not solved for).
+    let mut angles = vec![0.0; n];
+    angles[0] = spans[0].chord_angle;
+    if !closed {
+        angles[n - 1] = spans[n_spans - 1].chord_angle;
+    }
+    let lo = if closed { 0 } else { 1 };
+    let hi = if closed { n } else { n - 1 };
+    for k in lo..hi {
+        let prev_span = &spans[(k + n_spans - 1) % n_spans];
+        let next_span = &spans[k % n_spans];
+        // Seed from the bisector of the adjacent chord angles.
+        angles[k] = prev_span.chord_angle + wrap_angle(next_span.chord_angle - prev_span.chord_angle) * 0.5;
+    }
+
+    'sweeps: for _ in 0..MAX_SWEEPS {
+        let mut max_residual = 0.0f64;
+        for k in lo..hi {
+            let prev_k = if k == 0 { n - 1 } else { k - 1 };
+            let next_k = (k + 1) % n;
+            let prev_span = &spans[(k + n_spans - 1) % n_spans];
+            let next_span = &spans[k % n_spans];
+            let r = relax_knot(&mut angles, k, prev_k, next_k, prev_span, next_span);
+            max_residual = max_residual.max(r.abs());
+        }
+        if max_residual < CURVATURE_TOL {
+            break 'sweeps;
+        }
+    }
+
+    for (i, span) in spans.iter().enumerate() {
+        let p0 = points[i];
+        let p1 = points[(i + 1) % n];
+        let th0 = wrap_angle(angles[i] - span.chord_angle);
+        let th1 = wrap_angle(span.chord_angle - angles[(i + 1) % n]);
+        let params = EulerParams::from_angles(th0, th1);
+        let es = EulerSeg::from_params(p0, p1, params);
+        ArcSegment::lower_espc(&es, &mut path, 0.0..1.0, 0.0, tolerance);
+    }
+
+    path
+}
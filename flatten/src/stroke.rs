@@ -10,14 +10,14 @@ use std::fmt::Write;
 use std::{f64::consts::PI, ops::Range};
 
 use kurbo::{
-    common::solve_quadratic, Affine, Arc, BezPath, Cap, CubicBez, Join, Line, PathEl, PathSeg,
-    Point, QuadBez, Stroke, Vec2,
+    common::solve_quadratic, flatten, Affine, Arc, BezPath, Cap, CubicBez, Join, Line, PathEl,
+    PathSeg, Point, QuadBez, Rect, Stroke, Vec2,
 };
 
 use crate::{
     arc_segment::ArcSegment,
-    euler::{CubicToEulerIter, EulerSeg},
-    flatten::flatten_offset,
+    euler::{CubicToEulerIter, EulerParams, EulerSeg},
+    flatten::{flatten_offset, flatten_offset_dashed, DashEvent},
 };
 
 pub trait Lowering: Sized + Copy + Clone + core::fmt::Debug {
@@ -62,6 +62,16 @@ pub trait Lowering: Sized + Copy + Clone + core::fmt::Debug {
     fn reverse(self) -> Self;
 
     fn end_point(self) -> Point;
+
+    /// Map this primitive through `affine`.
+    ///
+    /// Exact for `Line`/`QuadSegment`/`QuadBez`, whose control points are
+    /// affine-covariant. `ArcSegment` can only represent circular arcs, so a
+    /// non-similarity `affine` (anisotropic scale or shear) is applied
+    /// approximately: endpoints transform exactly and the arc keeps its
+    /// subtended angle over the transformed chord, rather than following the
+    /// true (elliptical) result.
+    fn transform(self, affine: Affine) -> Self;
 }
 
 #[derive(Debug)]
@@ -97,17 +107,16 @@ impl<L: Lowering> Default for StrokeContour<L> {
 }
 
 impl<L: Lowering> LoweredPath<L> {
-    fn move_to(&mut self, p: Point) {
+    pub fn move_to(&mut self, p: Point) {
         self.last_pt = p;
     }
 
-    fn line_to(&mut self, p: Point) {
+    pub fn line_to(&mut self, p: Point) {
         self.path.push(L::line(Line::new(self.last_pt, p)));
         self.last_pt = p;
     }
 
-    #[allow(unused)]
-    fn to_bez(&self) -> BezPath {
+    pub fn to_bez(&self) -> BezPath {
         let mut result = BezPath::new();
         for seg in &self.path {
             seg.to_bez(&mut result);
@@ -115,6 +124,69 @@ impl<L: Lowering> LoweredPath<L> {
         result
     }
 
+    /// Rasterize the expanded outline into a triangle mesh carrying
+    /// per-vertex coverage, so the fragment shader can use `coverage`
+    /// directly as alpha and strokes can be filled on the GPU without MSAA.
+    ///
+    /// Interior fill triangles (a fan per closed ring) get coverage 1
+    /// everywhere. Every boundary edge, including the arcs already produced
+    /// by round joins and caps, also gets a thin outer ramp strip offset
+    /// outward along the edge normal by half a device pixel, with coverage
+    /// fading from 1 at the edge to 0 at the offset copy; because every ring
+    /// edge (join and cap arcs included) gets the same treatment, adjacent
+    /// ramps share endpoints and no seam appears at join/cap boundaries.
+    pub fn to_aa_mesh(&self, device_scale: f64) -> Vec<Vertex> {
+        let half_px = 0.5 / device_scale;
+        let mut verts = vec![];
+        for ring in flatten_to_rings(&self.to_bez(), 0.1 / device_scale) {
+            let n = ring.len();
+            if n < 3 {
+                continue;
+            }
+            // Interior fan. The outlines produced by stroke expansion are a
+            // single simple ring rather than a general polygon, so a fan
+            // from the first vertex is sufficient (same assumption made by
+            // `to_svg`'s non-zero winding fill).
+            for i in 1..n - 1 {
+                verts.push(Vertex::new(ring[0], 1.0));
+                verts.push(Vertex::new(ring[i], 1.0));
+                verts.push(Vertex::new(ring[i + 1], 1.0));
+            }
+            // Outer antialiasing ramp, one quad (as two triangles) per edge.
+            for i in 0..n {
+                let p0 = ring[i];
+                let p1 = ring[(i + 1) % n];
+                let edge = p1 - p0;
+                if edge.hypot() < 1e-12 {
+                    continue;
+                }
+                let normal = Vec2::new(edge.y, -edge.x).normalize();
+                let p0_out = p0 + normal * half_px;
+                let p1_out = p1 + normal * half_px;
+                verts.push(Vertex::new(p0, 1.0));
+                verts.push(Vertex::new(p1, 1.0));
+                verts.push(Vertex::new(p1_out, 0.0));
+                verts.push(Vertex::new(p0, 1.0));
+                verts.push(Vertex::new(p1_out, 0.0));
+                verts.push(Vertex::new(p0_out, 0.0));
+            }
+        }
+        verts
+    }
+
+    /// Same geometry as `to_aa_mesh`, grouped into explicit `AaTri` triangles
+    /// instead of a flat vertex stream.
+    pub fn to_aa_tris(&self, device_scale: f64) -> Vec<AaTri> {
+        self.to_aa_mesh(device_scale)
+            .chunks_exact(3)
+            .map(|c| AaTri {
+                p0: c[0],
+                p1: c[1],
+                p2: c[2],
+            })
+            .collect()
+    }
+
     pub fn to_svg(&self) -> String {
         let mut svg = String::new();
         let mut last_pt = None;
@@ -125,6 +197,36 @@ impl<L: Lowering> LoweredPath<L> {
         svg
     }
 
+    /// Map every primitive (and the tracked `last_pt`) through `affine`, via
+    /// `Lowering::transform`.
+    pub fn transform(&self, affine: Affine) -> Self {
+        LoweredPath {
+            path: self.path.iter().map(|seg| seg.transform(affine)).collect(),
+            last_pt: affine * self.last_pt,
+        }
+    }
+
+    /// Clip this outline against `rect` (typically the viewport enlarged by
+    /// a guard band), dropping geometry entirely outside it. The edges
+    /// Sutherland–Hodgman introduces at the boundary are straight by
+    /// construction, so unlike `transform` the result is always
+    /// `LoweredPath<Line>` regardless of `L`; `tolerance` controls how
+    /// finely curved/arc primitives are flattened before clipping.
+    pub fn clip(&self, rect: Rect, tolerance: f64) -> LoweredPath<Line> {
+        let mut out = LoweredPath::default();
+        for ring in crate::clip::clip_path(&self.to_bez(), tolerance, rect) {
+            if ring.len() < 2 {
+                continue;
+            }
+            out.move_to(ring[0]);
+            for &p in &ring[1..] {
+                out.line_to(p);
+            }
+            out.line_to(ring[0]);
+        }
+        out
+    }
+
     fn extend(&mut self, other: &Self) {
         if !other.path.is_empty() {
             self.path.extend(&other.path);
@@ -139,6 +241,122 @@ impl<L: Lowering> LoweredPath<L> {
             self.last_pt = self.path.last().unwrap().end_point();
         }
     }
+
+    /// Serialize this outline as a standalone SVG document: the outline
+    /// itself as a single filled `<path>` (same winding convention as
+    /// `to_svg`), plus, when `opts.markers` is set, one small filled circle
+    /// per lowered segment's endpoint — the same subdivision markers
+    /// `anims::draw_subdivisions` overlays on a live `Scene` — so the
+    /// flattener's vertex placement is visible too, not just the outline's
+    /// silhouette. Meant for reproducible paper figures and regression
+    /// diffs without running the animation harness; `opts.fill` lets
+    /// several lowerings (e.g. `Line` vs `ArcSegment`, or `strong` vs weak
+    /// `StrokeOpts`) be exported as separately colored layers and overlaid.
+    pub fn to_svg_doc(&self, opts: &SvgExportOpts) -> String {
+        let Rect { x0, y0, x1, y1 } = opts.viewport;
+        let (width, height) = (x1 - x0, y1 - y0);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg width=\"{width}\" height=\"{height}\" viewBox=\"{x0} {y0} {width} {height}\" xmlns=\"http://www.w3.org/2000/svg\">"
+        );
+        let _ = writeln!(out, "  <path d='{}' fill=\"{}\"/>", self.to_svg(), opts.fill);
+        if opts.markers {
+            for seg in &self.path {
+                let p = seg.end_point();
+                let _ = writeln!(
+                    out,
+                    "  <circle cx=\"{:.3}\" cy=\"{:.3}\" r=\"{}\" fill=\"{}\"/>",
+                    p.x, p.y, opts.marker_radius, opts.marker_fill
+                );
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Serialize this outline as a minimal standalone Encapsulated
+    /// PostScript document: a bounding box plus the outline's
+    /// moveto/lineto/curveto commands (via `to_bez`, promoting the rare
+    /// quadratic segment to a cubic since PostScript has no quadratic
+    /// operator) and a single `fill`. Coordinates are emitted as-is —
+    /// PostScript's y-up convention isn't corrected for — so this document
+    /// renders vertically mirrored relative to `to_svg_doc`'s; that's fine
+    /// for dropping into a LaTeX figure (which positions the EPS by its
+    /// bounding box, not its internal orientation) but means this isn't a
+    /// drop-in replacement if an upright on-screen preview is needed.
+    /// `opts.markers`/`opts.marker_fill` are ignored; only `opts.viewport`
+    /// is used, for the bounding box.
+    pub fn to_eps_doc(&self, opts: &SvgExportOpts) -> String {
+        let Rect { x0, y0, x1, y1 } = opts.viewport;
+        let mut out = String::new();
+        let _ = writeln!(out, "%!PS-Adobe-3.0 EPSF-3.0");
+        let _ = writeln!(out, "%%BoundingBox: {x0:.0} {y0:.0} {x1:.0} {y1:.0}");
+        let mut cur = Point::ORIGIN;
+        for el in self.to_bez().elements() {
+            match *el {
+                PathEl::MoveTo(p) => {
+                    let _ = writeln!(out, "{:.3} {:.3} moveto", p.x, p.y);
+                    cur = p;
+                }
+                PathEl::LineTo(p) => {
+                    let _ = writeln!(out, "{:.3} {:.3} lineto", p.x, p.y);
+                    cur = p;
+                }
+                PathEl::QuadTo(q, p1) => {
+                    // PostScript has no quadratic operator; promote to the
+                    // equivalent cubic (standard degree-raise formula).
+                    let c1 = cur + (2.0 / 3.0) * (q - cur);
+                    let c2 = p1 + (2.0 / 3.0) * (q - p1);
+                    let _ = writeln!(
+                        out,
+                        "{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} curveto",
+                        c1.x, c1.y, c2.x, c2.y, p1.x, p1.y
+                    );
+                    cur = p1;
+                }
+                PathEl::CurveTo(p1, p2, p3) => {
+                    let _ = writeln!(
+                        out,
+                        "{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} curveto",
+                        p1.x, p1.y, p2.x, p2.y, p3.x, p3.y
+                    );
+                    cur = p3;
+                }
+                PathEl::ClosePath => out.push_str("closepath\n"),
+            }
+        }
+        out.push_str("fill\n%%EOF\n");
+        out
+    }
+}
+
+/// Options for `LoweredPath::to_svg_doc`/`to_eps_doc`.
+#[derive(Clone, Copy, Debug)]
+pub struct SvgExportOpts {
+    /// The document's `viewBox` (SVG) or bounding box (EPS).
+    pub viewport: Rect,
+    /// Fill color for the outline, as a CSS color string (SVG only).
+    pub fill: &'static str,
+    /// Draw a circle at every lowered segment's endpoint (SVG only).
+    pub markers: bool,
+    /// Marker radius, in document units; only read when `markers` is set.
+    pub marker_radius: f64,
+    /// Marker fill color, as a CSS color string; only read when `markers`
+    /// is set.
+    pub marker_fill: &'static str,
+}
+
+impl Default for SvgExportOpts {
+    fn default() -> Self {
+        SvgExportOpts {
+            viewport: Rect::new(0.0, 0.0, 1024.0, 1024.0),
+            fill: "black",
+            markers: false,
+            marker_radius: 3.0,
+            marker_fill: "#19194d",
+        }
+    }
 }
 
 impl Lowering for Line {
@@ -206,6 +424,10 @@ impl Lowering for Line {
     fn end_point(self) -> Point {
         self.p1
     }
+
+    fn transform(self, affine: Affine) -> Self {
+        affine * self
+    }
 }
 
 impl Lowering for ArcSegment {
@@ -237,9 +459,10 @@ impl Lowering for ArcSegment {
             let svg_arc = self.to_svg_arc();
             _ = write!(
                 svg,
-                "A{:.3} {:.3} 0 {} {} {:.3} {:.3}",
+                "A{:.3} {:.3} {:.3} {} {} {:.3} {:.3}",
                 svg_arc.radii.x,
                 svg_arc.radii.y,
+                svg_arc.x_rotation.to_degrees(),
                 svg_arc.large_arc as usize,
                 svg_arc.sweep as usize,
                 svg_arc.to.x,
@@ -324,6 +547,298 @@ impl Lowering for ArcSegment {
     fn end_point(self) -> Point {
         self.p1
     }
+
+    fn transform(self, affine: Affine) -> Self {
+        // `k0` is the arc's subtended angle, which any similarity transform
+        // (translate/rotate/uniform-scale) leaves unchanged regardless of
+        // the chord's new length, except that a reflection (negative
+        // determinant) reverses the arc's winding; for a non-similarity
+        // `affine` the true image isn't a circular arc at all, so keeping
+        // `|k0|` and just transforming the endpoints is the least-wrong
+        // approximation.
+        let [a, b, c, d, _, _] = affine.as_coeffs();
+        let k0 = if a * d - b * c < 0.0 { -self.k0 } else { self.k0 };
+        ArcSegment::new(affine * self.p0, affine * self.p1, k0)
+    }
+}
+
+/// A quadratic Bézier segment, used as a `Lowering` target for GPU
+/// rasterizers that want quadratics rather than lines or arcs.
+#[derive(Clone, Copy, Debug)]
+pub struct QuadSegment {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+}
+
+impl QuadSegment {
+    pub fn new(p0: Point, p1: Point, p2: Point) -> Self {
+        QuadSegment { p0, p1, p2 }
+    }
+
+    /// The quadratic through `p0` and `p2` that also passes through `mid`
+    /// at `t = 0.5`.
+    fn through_midpoint(p0: Point, mid: Point, p2: Point) -> Self {
+        let ctrl = (mid.to_vec2() * 2.0 - (p0.to_vec2() + p2.to_vec2()) * 0.5).to_point();
+        QuadSegment::new(p0, ctrl, p2)
+    }
+}
+
+impl Lowering for QuadSegment {
+    fn to_bez(&self, path: &mut BezPath) {
+        let need_move_to = match path.elements().last() {
+            None => true,
+            Some(el) => el.end_point() != Some(self.p0),
+        };
+        if need_move_to {
+            path.move_to(self.p0);
+        }
+        path.quad_to(self.p1, self.p2);
+    }
+
+    fn to_svg_string(&self, svg: &mut String, last_pt: Option<Point>) {
+        if Some(self.p0) != last_pt {
+            _ = write!(svg, "M{:.3} {:.3}", self.p0.x, self.p0.y);
+        }
+        _ = write!(
+            svg,
+            "Q{:.3} {:.3} {:.3} {:.3}",
+            self.p1.x, self.p1.y, self.p2.x, self.p2.y
+        );
+    }
+
+    fn line(line: Line) -> Self {
+        QuadSegment::new(line.p0, line.p0.midpoint(line.p1), line.p1)
+    }
+
+    fn lower_arc(arc: &ArcSegment, segs: &mut Vec<Self>, tol: f64) {
+        // Reuse Line's arc subdivision to get a tolerance-conforming point
+        // count, then fit a degenerate (straight) quadratic between each
+        // consecutive pair.
+        let mut lines = vec![];
+        Line::lower_arc(arc, &mut lines, tol);
+        for line in lines {
+            segs.push(QuadSegment::line(line));
+        }
+    }
+
+    fn lower_espc(
+        es: &EulerSeg,
+        path: &mut LoweredPath<Self>,
+        range: Range<f64>,
+        dist: f64,
+        tol: f64,
+    ) {
+        // Error estimate via the curvature-derivative (`k1`) magnitude,
+        // the Euler-segment analog of a cubic's third-derivative term;
+        // split count follows the same cube-root law as fitting quadratics
+        // to a cubic within `tol`.
+        let range_size = range.end - range.start;
+        let arclen = es.p0.distance(es.p1) / es.params.ch;
+        let est_err =
+            (1. / 384.) / tol * es.params.k1.abs() * (arclen + 0.4 * (es.params.k1 * dist).abs());
+        let n_subdiv = est_err.cbrt() * range_size;
+        let n = (n_subdiv.ceil() as usize).max(1);
+        let dt = range_size / n as f64;
+        let mut p0 = path.last_pt;
+        for i in 0..n {
+            let t0 = range.start + i as f64 * dt;
+            let t1 = t0 + dt;
+            let mid = es.eval_with_offset(t0 + 0.5 * dt, dist);
+            let p1 = es.eval_with_offset(t1, dist);
+            path.path.push(QuadSegment::through_midpoint(p0, mid, p1));
+            p0 = p1;
+        }
+        path.last_pt = p0;
+    }
+
+    fn reverse(self) -> Self {
+        QuadSegment::new(self.p2, self.p1, self.p0)
+    }
+
+    fn end_point(self) -> Point {
+        self.p2
+    }
+
+    fn transform(self, affine: Affine) -> Self {
+        QuadSegment::new(affine * self.p0, affine * self.p1, affine * self.p2)
+    }
+}
+
+/// The control point of the quadratic through `p0`/`p1` tangent to `tan0`
+/// and `tan1` respectively: the intersection of the two tangent lines, the
+/// standard construction for fitting a quadratic to matching endpoint
+/// tangents (same cross-product solve `do_join` uses for a miter point).
+/// Falls back to the midpoint when the tangents are (nearly) parallel.
+fn quad_ctrl(p0: Point, tan0: Vec2, p1: Point, tan1: Vec2) -> Point {
+    let cross = tan0.cross(tan1);
+    if cross.abs() > 1e-9 {
+        let h = tan0.cross(p1 - p0) / cross;
+        p1 - tan1 * h
+    } else {
+        p0.midpoint(p1)
+    }
+}
+
+impl Lowering for QuadBez {
+    fn to_bez(&self, path: &mut BezPath) {
+        let need_move_to = match path.elements().last() {
+            None => true,
+            Some(el) => el.end_point() != Some(self.p0),
+        };
+        if need_move_to {
+            path.move_to(self.p0);
+        }
+        path.quad_to(self.p1, self.p2);
+    }
+
+    fn to_svg_string(&self, svg: &mut String, last_pt: Option<Point>) {
+        if Some(self.p0) != last_pt {
+            _ = write!(svg, "M{:.3} {:.3}", self.p0.x, self.p0.y);
+        }
+        _ = write!(
+            svg,
+            "Q{:.3} {:.3} {:.3} {:.3}",
+            self.p1.x, self.p1.y, self.p2.x, self.p2.y
+        );
+    }
+
+    fn line(line: Line) -> Self {
+        QuadBez::new(line.p0, line.p0.midpoint(line.p1), line.p1)
+    }
+
+    fn lower_arc(arc: &ArcSegment, segs: &mut Vec<Self>, tol: f64) {
+        // Same angular error bound as `Line::lower_arc`; each angular
+        // substep is fit with a real (non-degenerate) quadratic via the
+        // endpoint tangents, the standard construction for approximating a
+        // circular arc with a quadratic Bézier.
+        let chord = arc.p1 - arc.p0;
+        let chord_len = chord.length();
+        let n_frac = arc.k0.abs() * (0.125 * chord_len / tol).sqrt();
+        if n_frac <= 1.0 {
+            segs.push(QuadBez::line(Line::new(arc.p0, arc.p1)));
+            return;
+        }
+        let n_ceil = n_frac.ceil();
+        let n = n_ceil as usize;
+        let (half_s, half_c) = (0.5 * arc.k0).sin_cos();
+        let scale = 0.5 / half_s;
+        let point_and_tangent = |th: f64| {
+            let u = th.sin() * scale + 0.5;
+            let v = (th.cos() - half_c) * scale;
+            let p = arc.p0 + Vec2::new(u * chord.x - v * chord.y, u * chord.y + v * chord.x);
+            let (s, c) = th.sin_cos();
+            let tan = Vec2::new(c * chord.x + s * chord.y, c * chord.y - s * chord.x);
+            (p, tan)
+        };
+        let (mut p0, mut tan0) = point_and_tangent(-0.5 * arc.k0);
+        for i in 1..=n {
+            let th = ((i as f64) / n_ceil - 0.5) * arc.k0;
+            let (p1, tan1) = point_and_tangent(th);
+            segs.push(QuadBez::new(p0, quad_ctrl(p0, tan0, p1, tan1), p1));
+            p0 = p1;
+            tan0 = tan1;
+        }
+    }
+
+    fn lower_espc(
+        es: &EulerSeg,
+        path: &mut LoweredPath<Self>,
+        range: Range<f64>,
+        dist: f64,
+        tol: f64,
+    ) {
+        // Same cube-root subdivision law as `QuadSegment`, but the control
+        // point is placed at the intersection of the endpoint tangents
+        // (from the Euler-spiral tangent angles) rather than fit through
+        // the midpoint, for a tighter quadratic approximation.
+        let range_size = range.end - range.start;
+        let arclen = es.p0.distance(es.p1) / es.params.ch;
+        let est_err =
+            (1. / 384.) / tol * es.params.k1.abs() * (arclen + 0.4 * (es.params.k1 * dist).abs());
+        let n_subdiv = est_err.cbrt() * range_size;
+        let n = (n_subdiv.ceil() as usize).max(1);
+        let dt = range_size / n as f64;
+        let chord = es.p1 - es.p0;
+        let tangent_at = |t: f64| {
+            let (ls, lc) = es.params.eval_th(t).sin_cos();
+            Vec2::new(chord.x * lc + chord.y * ls, chord.y * lc - chord.x * ls)
+        };
+        let mut p0 = path.last_pt;
+        let mut tan0 = tangent_at(range.start);
+        for i in 0..n {
+            let t0 = range.start + i as f64 * dt;
+            let t1 = t0 + dt;
+            let p1 = es.eval_with_offset(t1, dist);
+            let tan1 = tangent_at(t1);
+            path.path.push(QuadBez::new(p0, quad_ctrl(p0, tan0, p1, tan1), p1));
+            p0 = p1;
+            tan0 = tan1;
+        }
+        path.last_pt = p0;
+    }
+
+    fn reverse(self) -> Self {
+        QuadBez::new(self.p2, self.p1, self.p0)
+    }
+
+    fn end_point(self) -> Point {
+        self.p2
+    }
+
+    fn transform(self, affine: Affine) -> Self {
+        affine * self
+    }
+}
+
+/// A mesh vertex with per-vertex coverage, for antialiased fill without MSAA.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub x: f64,
+    pub y: f64,
+    pub coverage: f64,
+}
+
+impl Vertex {
+    fn new(p: Point, coverage: f64) -> Self {
+        Vertex {
+            x: p.x,
+            y: p.y,
+            coverage,
+        }
+    }
+}
+
+/// One triangle of `to_aa_mesh`'s output, grouped for consumers (e.g. a GPU
+/// upload) that want explicit triangles rather than a flat vertex stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AaTri {
+    pub p0: Vertex,
+    pub p1: Vertex,
+    pub p2: Vertex,
+}
+
+/// Flatten `path` into closed point rings, one per subpath, for building a
+/// fill mesh. Mirrors `dilate::flatten_subpaths`, but every ring is always
+/// treated as closed: stroke expansion only ever emits filled outlines.
+fn flatten_to_rings(path: &BezPath, tolerance: f64) -> Vec<Vec<Point>> {
+    let mut rings = vec![];
+    let mut pts = vec![];
+    flatten(path, tolerance, |el| match el {
+        PathEl::MoveTo(p) => {
+            if !pts.is_empty() {
+                rings.push(std::mem::take(&mut pts));
+            }
+            pts.push(p);
+        }
+        PathEl::LineTo(p) => pts.push(p),
+        PathEl::ClosePath => (),
+        _ => unreachable!("kurbo::flatten only emits MoveTo/LineTo/ClosePath"),
+    });
+    if !pts.is_empty() {
+        rings.push(pts);
+    }
+    rings
 }
 
 // Copied from kurbo, the method is private
@@ -369,6 +884,123 @@ fn pathseg_tangents(seg: &PathSeg) -> (Vec2, Vec2) {
     }
 }
 
+/// Options controlling the details of stroke-to-fill expansion.
+///
+/// `stroke_undashed` always uses the default options; `stroke_undashed_opt`
+/// is the entry point for callers (such as the beztoy toy) that want to
+/// control these knobs directly.
+#[derive(Clone, Copy, Debug)]
+pub struct StrokeOpts {
+    /// When `true` (the default), each Euler segment is offset using the
+    /// exact cusp/evolute-aware logic in `StrokeContour::do_euler_seg`,
+    /// which is the geometrically correct behavior near curvature
+    /// extrema and cusps.
+    ///
+    /// When `false`, the offset curve is lowered directly over the whole
+    /// `[0, 1]` range without cusp detection. This is cheaper, but can
+    /// produce locally self-intersecting or folded-over geometry near
+    /// cusps; it exists mainly so the toy and benchmarks can compare
+    /// against the unoptimized behavior.
+    pub strong: bool,
+
+    /// When `true`, `stroke_undashed_opt_diag` records cusp/evolute
+    /// bookkeeping into the `StrokeDiagnostics` it returns. When `false`
+    /// (the default), that recording is skipped entirely so the hot path
+    /// stays allocation-free.
+    pub diagnostics: bool,
+
+    /// The pen shape, as a linear map `M` from the unit circle (default:
+    /// `Affine::IDENTITY`, a circular pen).
+    ///
+    /// A non-identity `M` makes `stroke_undashed_opt`/`_diag` expand `path`
+    /// with an elliptical rather than circular pen, by stroking `M⁻¹·path`
+    /// with the ordinary unit-circle machinery and mapping the result by
+    /// `M`: the envelope of `M`'s ellipse swept along `path` equals `M`
+    /// applied to the envelope of the unit circle swept along `M⁻¹·path`.
+    /// This is the correct way to stroke under a non-uniform-scale/skew
+    /// render transform (set `M` to that transform's linear part) and also
+    /// covers `vector-effect: non-scaling-stroke` (leave `M` at identity and
+    /// apply the render transform to the centerline before stroking
+    /// instead, so the pen itself stays circular).
+    pub pen_transform: Affine,
+
+    /// The winding rule the caller intends to fill `LoweredPath::to_bez`'s
+    /// output with (default `FillRule::NonZero`).
+    ///
+    /// A large offset relative to curvature (particularly near cusps and in
+    /// the evolute region `lower_es_evolute_arc` handles) can make the
+    /// forward and backward contours of a single stroke overlap into
+    /// self-intersecting lobes. This crate doesn't resolve that overlap
+    /// into a non-self-intersecting outline; instead, like Ruffle's shape
+    /// utilities, it leaves the choice of winding rule to the renderer so
+    /// the overlap composites correctly (`NonZero` keeps every lobe filled
+    /// solid; `EvenOdd` punches out doubly-wound regions). This field is
+    /// purely advisory — `stroke_undashed_opt`/`to_bez` don't change their
+    /// geometry based on it — so a caller such as the beztoy toy can carry
+    /// its chosen rule alongside the other stroke options and apply it when
+    /// filling the result.
+    pub fill_rule: FillRule,
+}
+
+/// The winding rule to fill a stroke-expanded outline with. See
+/// `StrokeOpts::fill_rule`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// A point is inside the fill if the outline's winding number around it
+    /// is nonzero. Fills every self-intersecting lobe solid, matching how a
+    /// non-self-intersecting stroke outline is normally rendered.
+    #[default]
+    NonZero,
+    /// A point is inside the fill if the outline crosses any ray to it an
+    /// odd number of times. Punches out regions covered an even number of
+    /// times, which can "cancel out" overlapping lobes near cusps.
+    EvenOdd,
+}
+
+impl Default for StrokeOpts {
+    fn default() -> Self {
+        StrokeOpts {
+            strong: true,
+            diagnostics: false,
+            pen_transform: Affine::IDENTITY,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+}
+
+/// Map every point of `path` through `affine`.
+fn transform_path_els(path: impl IntoIterator<Item = PathEl>, affine: Affine) -> Vec<PathEl> {
+    path.into_iter()
+        .map(|el| match el {
+            PathEl::MoveTo(p) => PathEl::MoveTo(affine * p),
+            PathEl::LineTo(p) => PathEl::LineTo(affine * p),
+            PathEl::QuadTo(p1, p2) => PathEl::QuadTo(affine * p1, affine * p2),
+            PathEl::CurveTo(p1, p2, p3) => PathEl::CurveTo(affine * p1, affine * p2, affine * p3),
+            PathEl::ClosePath => PathEl::ClosePath,
+        })
+        .collect()
+}
+
+/// Cusp/curvature-extremum metadata recorded by `stroke_undashed_opt_diag`,
+/// for rendering the evolute and parallel curves separately (as the paper's
+/// figures and the Vello flatten shader visualize Euler-spiral structure)
+/// and for asserting in tests that cusp handling triggers where expected.
+#[derive(Debug, Default, Clone)]
+pub struct StrokeDiagnostics {
+    /// Client-space cusp locations: points where an offset curve's radius
+    /// reached zero (a cusp of the offset, not necessarily of the
+    /// centerline), and points where a degenerate, colinear cubic reverses
+    /// direction along its centerline.
+    pub cusps: Vec<Point>,
+    /// Client-space `(start, end)` pairs bracketing each run where the
+    /// offset flipped to the evolute branch past a cusp.
+    pub evolute_ranges: Vec<(Point, Point)>,
+    /// `(k0, k1)` of every `EulerSeg` a curved centerline segment was split
+    /// into: the curvature at the segment's midpoint and its rate of change
+    /// along it, in the units of `EulerParams::curvature`.
+    pub euler_curvatures: Vec<(f64, f64)>,
+}
+
 /// Internal structure used for creating strokes.
 struct StrokeCtx<L: Lowering> {
     // As a possible future optimization, we might not need separate storage
@@ -386,14 +1018,75 @@ struct StrokeCtx<L: Lowering> {
     // Precomputation of the join threshold, to optimize per-join logic.
     // If hypot < (hypot + dot) * join_thresh, omit join altogether.
     join_thresh: f64,
+    opts: StrokeOpts,
+    // `None` when `opts.diagnostics` is false, so the hot path never pushes
+    // to (or allocates) these `Vec`s.
+    diagnostics: Option<StrokeDiagnostics>,
 }
 
 /// Version of stroke expansion for styles with no dashes.
+///
+/// Honors the full range of `Stroke` cap (`Butt`/`Round`/`Square`, via
+/// `StrokeCtx::finish`/`finish_closed`) and join (`Bevel`/`Miter`/`Round`,
+/// via `StrokeCtx::do_join`) styles, for any `Lowering` target — not just
+/// the `Cap::Butt` style the `anims` demos happen to use. Only the
+/// outer/convex side of a turn gets cap/join geometry, so a concave corner
+/// can't self-overlap; a miter falls back to a bevel once its length would
+/// exceed `style.miter_limit`. For `L = ArcSegment`, round caps and round
+/// joins come out as a single exact arc rather than a flattened polyline,
+/// since `ArcSegment::lower_arc` stores the arc directly instead of
+/// subdividing it.
+///
+/// This coverage predates this doc comment; nothing here changed its
+/// behavior.
 pub fn stroke_undashed<L: Lowering>(
     path: impl IntoIterator<Item = PathEl>,
     style: &Stroke,
     tolerance: f64,
 ) -> LoweredPath<L> {
+    stroke_undashed_opt(path, style, tolerance, StrokeOpts::default())
+}
+
+/// Version of `stroke_undashed` with explicit `StrokeOpts`.
+pub fn stroke_undashed_opt<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    style: &Stroke,
+    tolerance: f64,
+    opts: StrokeOpts,
+) -> LoweredPath<L> {
+    stroke_undashed_opt_diag(path, style, tolerance, opts).0
+}
+
+/// Version of `stroke_undashed_opt` that additionally returns a
+/// `StrokeDiagnostics` sidecar when `opts.diagnostics` is set (otherwise the
+/// sidecar is returned empty, at no extra cost beyond the flag check).
+pub fn stroke_undashed_opt_diag<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    style: &Stroke,
+    tolerance: f64,
+    opts: StrokeOpts,
+) -> (LoweredPath<L>, StrokeDiagnostics) {
+    if opts.pen_transform != Affine::IDENTITY {
+        let pen_transform = opts.pen_transform;
+        let preimage = transform_path_els(path, pen_transform.inverse());
+        let circular_opts = StrokeOpts {
+            pen_transform: Affine::IDENTITY,
+            ..opts
+        };
+        let (lowered, mut diagnostics) =
+            stroke_undashed_opt_diag::<L>(preimage, style, tolerance, circular_opts);
+        if opts.diagnostics {
+            for p in &mut diagnostics.cusps {
+                *p = pen_transform * *p;
+            }
+            for (a, b) in &mut diagnostics.evolute_ranges {
+                *a = pen_transform * *a;
+                *b = pen_transform * *b;
+            }
+        }
+        return (lowered.transform(pen_transform), diagnostics);
+    }
+    let diagnostics = opts.diagnostics.then(StrokeDiagnostics::default);
     let mut ctx = StrokeCtx {
         join_thresh: 2.0 * tolerance / style.width,
         tolerance,
@@ -405,6 +1098,8 @@ pub fn stroke_undashed<L: Lowering>(
         start_tan: Default::default(),
         last_pt: Default::default(),
         last_tan: Default::default(),
+        opts,
+        diagnostics,
     };
     for el in path {
         let p0 = ctx.last_pt;
@@ -452,7 +1147,310 @@ pub fn stroke_undashed<L: Lowering>(
         }
     }
     ctx.finish(style);
-    ctx.output
+    (ctx.output, ctx.diagnostics.unwrap_or_default())
+}
+
+/// Stroke-expand a single open subpath with a width profile `width(s)`
+/// sampled at normalized arc length `s` in `[0, 1]` over the whole subpath,
+/// producing a tapered or calligraphic outline via
+/// `EulerSeg::flatten_variable_offset` (`base(s) + width(s)·n(s)` on one
+/// side, `-width(s)` on the other). `cap` is applied at both ends.
+///
+/// Unlike `stroke_undashed`, corners between input segments get no join
+/// geometry and `Stroke::join` is not consulted: on each side, the offset
+/// polyline runs from the last offset point before a corner straight to the
+/// next sample after it. If `path` has more than one subpath, only the
+/// first is stroked.
+///
+/// Where `width(s)` crosses zero, the two sides meet exactly at the spine,
+/// so the outline pinches cleanly there rather than self-intersecting;
+/// `flatten_variable_offset` handles this without any special-casing.
+pub fn stroke_variable_width<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    width: impl Fn(f64) -> f64,
+    cap: Cap,
+    tolerance: f64,
+) -> LoweredPath<L> {
+    let Some(segs) = path_to_euler_segs(path, tolerance).into_iter().next() else {
+        return LoweredPath::default();
+    };
+    let total_len: f64 = segs
+        .iter()
+        .map(|es| (es.p0 - es.p1).hypot() / es.params.ch)
+        .sum();
+    if total_len <= 0.0 {
+        return LoweredPath::default();
+    }
+    let mut forward = LoweredPath::default();
+    let mut backward = LoweredPath::<L>::default();
+    let mut start_fwd_pt = Point::default();
+    let mut acc_len = 0.0;
+    for (i, es) in segs.iter().enumerate() {
+        let seg_len = (es.p0 - es.p1).hypot() / es.params.ch;
+        let s0 = acc_len;
+        let w = |t: f64| width((s0 + t * seg_len) / total_len);
+        let fwd_pts = es.flatten_variable_offset(&w, tolerance);
+        let bwd_pts = es.flatten_variable_offset(|t| -w(t), tolerance);
+        if i == 0 {
+            start_fwd_pt = fwd_pts[0];
+            forward.move_to(fwd_pts[0]);
+            backward.move_to(bwd_pts[0]);
+        }
+        for &p in &fwd_pts[1..] {
+            forward.line_to(p);
+        }
+        for &p in &bwd_pts[1..] {
+            backward.line_to(p);
+        }
+        acc_len += seg_len;
+    }
+    let mut output = LoweredPath::default();
+    output.extend(&forward);
+    let start_pt = segs[0].p0;
+    let end_pt = segs.last().unwrap().p1;
+    let end_norm = end_pt - backward.last_pt;
+    match cap {
+        Cap::Butt => output.line_to(backward.last_pt),
+        Cap::Round => round_cap(&mut output, tolerance, end_pt, end_norm),
+        Cap::Square => square_cap(&mut output, end_pt, end_norm),
+    }
+    output.extend_rev(&backward);
+    let start_norm = start_pt - start_fwd_pt;
+    match cap {
+        Cap::Butt => output.line_to(start_fwd_pt),
+        Cap::Round => round_cap(&mut output, tolerance, start_pt, start_norm),
+        Cap::Square => square_cap(&mut output, start_pt, start_norm),
+    }
+    output
+}
+
+/// Split `path` into Euler segments for arc-length-accurate dash walking,
+/// converting each `PathEl` the same way `stroke_undashed` does but without
+/// offsetting: lines become zero-curvature Euler segments, quads are raised
+/// to cubics, and a closing segment is synthesized for `ClosePath`.
+///
+/// Returns one `Vec<EulerSeg>` per subpath, so the caller can restart the
+/// dash phase at each `MoveTo`, matching SVG's `stroke-dasharray` semantics.
+fn path_to_euler_segs(path: impl IntoIterator<Item = PathEl>, tolerance: f64) -> Vec<Vec<EulerSeg>> {
+    let straight = |p0: Point, p1: Point| EulerSeg::from_params(p0, p1, EulerParams::from_angles(0.0, 0.0));
+    let mut subpaths = vec![];
+    let mut segs = vec![];
+    let mut start_pt = Point::ORIGIN;
+    let mut last_pt = Point::ORIGIN;
+    for el in path {
+        match el {
+            PathEl::MoveTo(p) => {
+                if !segs.is_empty() {
+                    subpaths.push(std::mem::take(&mut segs));
+                }
+                start_pt = p;
+                last_pt = p;
+            }
+            PathEl::LineTo(p1) => {
+                if p1 != last_pt {
+                    segs.push(straight(last_pt, p1));
+                    last_pt = p1;
+                }
+            }
+            PathEl::QuadTo(p1, p2) => {
+                if p1 != last_pt || p2 != last_pt {
+                    let c = QuadBez::new(last_pt, p1, p2).raise();
+                    segs.extend(CubicToEulerIter::new(c, tolerance));
+                    last_pt = p2;
+                }
+            }
+            PathEl::CurveTo(p1, p2, p3) => {
+                if p1 != last_pt || p2 != last_pt || p3 != last_pt {
+                    let c = CubicBez::new(last_pt, p1, p2, p3);
+                    segs.extend(CubicToEulerIter::new(c, tolerance));
+                    last_pt = p3;
+                }
+            }
+            PathEl::ClosePath => {
+                if last_pt != start_pt {
+                    segs.push(straight(last_pt, start_pt));
+                    last_pt = start_pt;
+                }
+            }
+        }
+    }
+    if !segs.is_empty() {
+        subpaths.push(segs);
+    }
+    subpaths
+}
+
+/// Build a polyline `BezPath` from a point list, for feeding a dash's
+/// flattened centerline back into `stroke_undashed`.
+fn polyline_bez(points: &[Point]) -> BezPath {
+    let mut path = BezPath::new();
+    path.move_to(points[0]);
+    for &p in &points[1..] {
+        path.line_to(p);
+    }
+    path
+}
+
+/// Version of stroke expansion for styles with a dash pattern.
+///
+/// Walks `path` by arc length (using the Euler-spiral parameterization of
+/// each segment, same as `flatten_offset_dashed`; since that parameterization
+/// is itself arc-length proportional, no separate arc-length-to-`t` inversion
+/// is needed to locate split points), cuts it into SVG-style on/off dashes
+/// honoring `style.dash_pattern`/`style.dash_offset`, and fully strokes each
+/// "on" dash (caps included) via `stroke_undashed`. A zero-length "on" dash
+/// (a dot) has no body to stroke, so it's rendered directly via `dot_cap`
+/// instead. The dash phase restarts at `style.dash_offset` for every
+/// subpath, matching SVG semantics; within a subpath it carries continuously
+/// across joins.
+///
+/// If `style.dash_pattern` is empty, or its entries sum to zero (a
+/// degenerate pattern that would otherwise spin forever trying to advance
+/// through the dash phase), this degenerates to `stroke_undashed`.
+pub fn stroke_dashed<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    style: &Stroke,
+    tolerance: f64,
+) -> LoweredPath<L> {
+    stroke_dashed_opt(path, style, tolerance, StrokeOpts::default())
+}
+
+/// Version of `stroke_dashed` with explicit `StrokeOpts`, applied to every
+/// dash run the same way `stroke_undashed_opt` applies them to the whole
+/// path.
+pub fn stroke_dashed_opt<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    style: &Stroke,
+    tolerance: f64,
+    opts: StrokeOpts,
+) -> LoweredPath<L> {
+    if style.dash_pattern.is_empty() || style.dash_pattern.iter().sum::<f64>() <= 0.0 {
+        return stroke_undashed_opt(path, style, tolerance, opts);
+    }
+    let mut output = LoweredPath::default();
+    for segs in path_to_euler_segs(path, tolerance) {
+        stroke_dashed_subpath::<L>(&mut output, segs, style, tolerance, opts);
+    }
+    output
+}
+
+/// Stroke-expand `path`, dispatching to `stroke_dashed_opt` or
+/// `stroke_undashed_opt` depending on whether `style` carries a (non-
+/// degenerate) dash pattern. This is the single entry point callers such as
+/// the beztoy toy should reach for once dashing is a user-facing option,
+/// rather than choosing between the two explicitly.
+pub fn stroke_opt<L: Lowering>(
+    path: impl IntoIterator<Item = PathEl>,
+    style: &Stroke,
+    tolerance: f64,
+    opts: StrokeOpts,
+) -> LoweredPath<L> {
+    stroke_dashed_opt(path, style, tolerance, opts)
+}
+
+/// Dash-walk a single subpath's Euler segments and stroke each resulting run.
+///
+/// On a closed subpath, if the walk is still mid-dash both when it starts
+/// and when it ends, those are really the same dash wrapping across the
+/// seam; they're merged here into a single run so it gets one pair of caps
+/// instead of being cut in two at an arbitrary point.
+fn stroke_dashed_subpath<L: Lowering>(
+    output: &mut LoweredPath<L>,
+    segs: Vec<EulerSeg>,
+    style: &Stroke,
+    tolerance: f64,
+    opts: StrokeOpts,
+) {
+    let (Some(first), Some(last)) = (segs.first(), segs.last()) else {
+        return;
+    };
+    let start_pt = first.p0;
+    let end_pt = last.p1;
+    let closed = start_pt == end_pt;
+    let mut runs: Vec<Vec<Point>> = vec![];
+    let mut dots: Vec<(Point, Vec2)> = vec![];
+    let mut current = vec![];
+    flatten_offset_dashed(
+        segs.into_iter(),
+        0.0,
+        &style.dash_pattern,
+        style.dash_offset,
+        tolerance,
+        |event| match event {
+            DashEvent::NewDash => {
+                if current.len() > 1 {
+                    runs.push(std::mem::take(&mut current));
+                } else {
+                    current.clear();
+                }
+            }
+            DashEvent::Point(pt) => current.push(pt),
+            DashEvent::Dot(pt, tangent) => dots.push((pt, tangent)),
+        },
+    );
+    if current.len() > 1 {
+        runs.push(current);
+    }
+    if closed && runs.len() > 1 {
+        let starts_on_dash = runs[0][0] == start_pt;
+        let ends_on_dash = runs[runs.len() - 1].last() == Some(&end_pt);
+        if starts_on_dash && ends_on_dash {
+            let first_run = runs.remove(0);
+            let last_run = runs.last_mut().unwrap();
+            last_run.pop();
+            last_run.extend(first_run);
+        }
+    }
+    for run in &runs {
+        output.extend(&stroke_undashed_opt::<L>(
+            &polyline_bez(run),
+            style,
+            tolerance,
+            opts,
+        ));
+    }
+    for (center, tangent) in dots {
+        dot_cap::<L>(
+            output,
+            tolerance,
+            center,
+            tangent,
+            style.width,
+            style.start_cap,
+        );
+    }
+}
+
+/// Render a zero-length "on" dash (an SVG dot) as the full pen shape at a
+/// point: a circle for `Cap::Round`, a square for `Cap::Square`. `Cap::Butt`
+/// dots are invisible, matching SVG: there's no cap extension to draw and no
+/// dash body to draw it from. Built by running the ordinary single-sided cap
+/// helpers twice, once from each side, since (unlike a normal dash end)
+/// there's no adjoining dash body to supply the other half of the shape.
+fn dot_cap<L: Lowering>(
+    output: &mut LoweredPath<L>,
+    tolerance: f64,
+    center: Point,
+    tangent: Vec2,
+    width: f64,
+    cap: Cap,
+) {
+    if matches!(cap, Cap::Butt) || tangent == Vec2::ZERO {
+        return;
+    }
+    let norm = 0.5 * width / tangent.hypot() * Vec2::new(-tangent.y, tangent.x);
+    output.move_to(center + norm);
+    match cap {
+        Cap::Butt => {}
+        Cap::Round => {
+            round_join(output, tolerance, center, norm, PI);
+            round_join(output, tolerance, center, -norm, PI);
+        }
+        Cap::Square => {
+            square_cap(output, center, norm);
+            square_cap(output, center, -norm);
+        }
+    }
 }
 
 fn round_cap<L: Lowering>(out: &mut LoweredPath<L>, tolerance: f64, center: Point, norm: Vec2) {
@@ -529,6 +1527,17 @@ impl<L: Lowering> StrokeCtx<L> {
         self.backward_path.main_path.path.clear();
     }
 
+    /// Join the previous segment's offset curves to the new one, branching on
+    /// `style.join`. Bevel connects the two offset endpoints directly; miter
+    /// intersects the offset lines to find the apex, falling back to bevel
+    /// when the miter length exceeds `style.miter_limit`; round fills the
+    /// notch with an arc. The sign of `ab.cross(cd)` picks which side (the
+    /// outer, convex one) gets the sharp join, and `join_thresh` skips the
+    /// join entirely when the turn is too shallow to need one, which also
+    /// keeps a near-straight miter from shooting off to a distant apex.
+    ///
+    /// This branching predates this doc comment; nothing here changed its
+    /// behavior.
     fn do_join(&mut self, style: &Stroke, tan0: Vec2) {
         let scale = 0.5 * style.width / tan0.hypot();
         let norm = scale * Vec2::new(-tan0.y, tan0.x);
@@ -659,10 +1668,33 @@ impl<L: Lowering> StrokeCtx<L> {
         let es_tol = self.tolerance;
         let lower_tol = self.tolerance;
         for es in CubicToEulerIter::new(c, es_tol) {
-            self.forward_path
-                .do_euler_seg(&es, -0.5 * style.width, lower_tol);
-            self.backward_path
-                .do_euler_seg(&es, 0.5 * style.width, lower_tol);
+            if self.opts.strong {
+                let fwd_h = -0.5 * style.width;
+                let bwd_h = 0.5 * style.width;
+                let fwd_range = self.forward_path.do_euler_seg(&es, fwd_h, lower_tol);
+                let bwd_range = self.backward_path.do_euler_seg(&es, bwd_h, lower_tol);
+                if let Some(diag) = &mut self.diagnostics {
+                    diag.euler_curvatures.push((es.params.k0, es.params.k1));
+                    for (range, h) in [(fwd_range, fwd_h), (bwd_range, bwd_h)] {
+                        if let Some(range) = range {
+                            diag.evolute_ranges.push((
+                                es.eval_with_offset(range.start, h),
+                                es.eval_with_offset(range.end, h),
+                            ));
+                            if range.end < 1.0 {
+                                diag.cusps.push(es.eval_with_offset(range.end, h));
+                            } else if range.start > 0.0 {
+                                diag.cusps.push(es.eval_with_offset(range.start, h));
+                            }
+                        }
+                    }
+                }
+            } else {
+                self.forward_path
+                    .do_euler_seg_weak(&es, -0.5 * style.width, lower_tol);
+                self.backward_path
+                    .do_euler_seg_weak(&es, 0.5 * style.width, lower_tol);
+            }
         }
         self.last_pt = c.p3;
     }
@@ -697,6 +1729,9 @@ impl<L: Lowering> StrokeCtx<L> {
                 let mt = 1.0 - t;
                 let z = mt * (mt * mt * p[0] + 3.0 * t * (mt * p[1] + t * p[2])) + t * t * t * p[3];
                 let p = ref_pt + z * ref_vec;
+                if let Some(diag) = &mut self.diagnostics {
+                    diag.cusps.push(p);
+                }
                 let tan = p - self.last_pt;
                 self.do_join(&style, tan);
                 self.do_line(&style, tan, p);
@@ -712,7 +1747,19 @@ impl<L: Lowering> StrokeCtx<L> {
 }
 
 impl<L: Lowering + core::fmt::Debug> StrokeContour<L> {
-    fn do_euler_seg(&mut self, es: &EulerSeg, h: f64, tolerance: f64) {
+    /// Lower an Euler segment's offset curve without cusp/evolute handling.
+    ///
+    /// This is the `StrokeOpts { strong: false }` path: it's cheaper than
+    /// `do_euler_seg`, but can fold over itself near a cusp rather than
+    /// routing through the evolute.
+    fn do_euler_seg_weak(&mut self, es: &EulerSeg, h: f64, tolerance: f64) {
+        L::lower_espc(es, &mut self.main_path, 0.0..1.0, h, tolerance);
+    }
+
+    /// Returns the sub-range of `es`'s parameter that was routed through the
+    /// evolute branch, if any (used by `do_cubic` to populate
+    /// `StrokeDiagnostics` when enabled).
+    fn do_euler_seg(&mut self, es: &EulerSeg, h: f64, tolerance: f64) -> Option<Range<f64>> {
         // TODO: make evolutes optional
         let chord_len = (es.p1 - es.p0).length();
         let cusp0 = es.params.curvature(0.) * h + chord_len;
@@ -731,7 +1778,9 @@ impl<L: Lowering + core::fmt::Debug> StrokeContour<L> {
                 let (evolute, rev_parallel) = self.start();
                 L::lower_es_evolute(es, evolute, t..1.0, tolerance);
                 L::lower_espc(es, rev_parallel, t..1.0, h, tolerance);
+                return Some(t..1.0);
             }
+            None
         } else {
             let (evolute, rev_parallel) = self.start();
             evolute.line_to(es.eval_evolute(0.));
@@ -741,6 +1790,7 @@ impl<L: Lowering + core::fmt::Debug> StrokeContour<L> {
                 self.finalize();
                 L::lower_espc(es, &mut self.main_path, t..1.0, h, tolerance);
             }
+            Some(0.0..t)
         }
     }
 
@@ -794,3 +1844,127 @@ pub fn stroke_main() {
     println!("{}", stroked.to_svg());
     //println!("{} segments", stroked.path.len());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cubic whose control points are exactly colinear but double back on
+    /// themselves (`p1` past `p3`'s side of `p0`, `p2` behind `p0`): this is
+    /// the classic degenerate case `do_cubic` routes to `do_linear`, whose
+    /// `solve_quadratic` on the projected points finds two interior cusps.
+    /// With `opts.diagnostics` on, those should show up in `diag.cusps`;
+    /// with it off (the default), the sidecar must come back empty at no
+    /// cost.
+    #[test]
+    fn diagnostics_record_cusp_on_looped_line() {
+        let make_path = || {
+            [
+                PathEl::MoveTo(Point::new(0.0, 0.0)),
+                PathEl::CurveTo(
+                    Point::new(2.0, 0.0),
+                    Point::new(-1.0, 0.0),
+                    Point::new(1.0, 0.0),
+                ),
+            ]
+        };
+        let style = Stroke::new(0.1);
+
+        let (_, diag) = stroke_undashed_opt_diag::<Line>(
+            make_path(),
+            &style,
+            0.01,
+            StrokeOpts {
+                diagnostics: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            diag.cusps.len(),
+            2,
+            "the looped, colinear control polygon has two interior cusps"
+        );
+
+        let (_, diag_off) =
+            stroke_undashed_opt_diag::<Line>(make_path(), &style, 0.01, StrokeOpts::default());
+        assert!(
+            diag_off.cusps.is_empty(),
+            "diagnostics should stay empty when opts.diagnostics is false"
+        );
+    }
+
+    /// Stroking a horizontal line with a pen transform `M = diag(1, b)`
+    /// should produce an elliptical pen whose perpendicular half-width away
+    /// from the caps is `b` times the circular pen's radius: the envelope of
+    /// `M`'s ellipse in the direction normal to a horizontal line is exactly
+    /// its y semi-axis. Butt caps keep the cap geometry from spilling
+    /// outside that y range, so `max(|y|)` over the whole stroke should
+    /// match `b * radius`.
+    #[test]
+    fn elliptical_pen_scales_perpendicular_width() {
+        let path = [
+            PathEl::MoveTo(Point::new(-10.0, 0.0)),
+            PathEl::LineTo(Point::new(10.0, 0.0)),
+        ];
+        let radius = 1.0;
+        let b = 3.0;
+        let style = Stroke::new(2.0 * radius).with_caps(Cap::Butt);
+        let opts = StrokeOpts {
+            pen_transform: Affine::scale_non_uniform(1.0, b),
+            ..Default::default()
+        };
+
+        let lowered: LoweredPath<Line> = stroke_undashed_opt(path, &style, 0.01, opts);
+        let max_abs_y = lowered
+            .path
+            .iter()
+            .flat_map(|line| [line.p0.y, line.p1.y])
+            .fold(0.0f64, |acc, y| acc.max(y.abs()));
+        assert!(
+            (max_abs_y - b * radius).abs() < 1e-6,
+            "expected max |y| {} to equal b * radius {}",
+            max_abs_y,
+            b * radius
+        );
+    }
+
+    /// A horizontal line stroked with an on/off dash pattern `[2, 2]` and
+    /// butt caps should leave the stroked outline entirely out of the "off"
+    /// gaps between dashes (butt caps don't spill past a dash's endpoints),
+    /// while `stroke_opt` with an empty pattern should match
+    /// `stroke_undashed_opt` exactly, confirming the dispatch in
+    /// `stroke_dashed_opt`/`stroke_opt` degenerates correctly.
+    #[test]
+    fn dashed_stroke_leaves_gaps_between_dashes() {
+        let path = [
+            PathEl::MoveTo(Point::new(0.0, 0.0)),
+            PathEl::LineTo(Point::new(10.0, 0.0)),
+        ];
+        let style = Stroke::new(0.2)
+            .with_caps(Cap::Butt)
+            .with_dashes(0.0, vec![2.0, 2.0]);
+
+        let lowered: LoweredPath<Line> =
+            stroke_opt(path, &style, 0.01, StrokeOpts::default());
+        let in_gap = |x: f64| (2.4..3.6).contains(&x) || (6.4..7.6).contains(&x);
+        assert!(
+            lowered
+                .path
+                .iter()
+                .all(|line| !in_gap(line.p0.x) && !in_gap(line.p1.x)),
+            "no stroked point should fall inside an 'off' dash gap"
+        );
+        assert!(!lowered.path.is_empty());
+
+        let undashed_style = Stroke::new(0.2).with_caps(Cap::Butt);
+        let plain: LoweredPath<Line> =
+            stroke_opt(path, &undashed_style, 0.01, StrokeOpts::default());
+        let expected: LoweredPath<Line> =
+            stroke_undashed_opt(path, &undashed_style, 0.01, StrokeOpts::default());
+        assert_eq!(
+            plain.path.len(),
+            expected.path.len(),
+            "stroke_opt with no dash pattern should degenerate to stroke_undashed_opt"
+        );
+    }
+}
@@ -7,34 +7,125 @@ use std::str::FromStr;
 use std::time::Instant;
 use std::{error::Error, io::Write};
 
-use kurbo::{BezPath, Cap, Join, Line, Stroke};
+use kurbo::{Affine, BezPath, Cap, Join, Line, PathEl, Rect, Stroke};
 use roxmltree::{Document, Node};
 
 use crate::arc_segment::ArcSegment;
-use crate::stroke::{stroke_undashed, LoweredPath, Lowering};
+use crate::stroke::{stroke_dashed, stroke_undashed, LoweredPath, Lowering};
 
 pub struct StyledPath {
     pub path: BezPath,
     pub style: Stroke,
-    pub scale: f64,
+    pub transform: Affine,
+    /// `vector-effect: non-scaling-stroke`: the centerline is still placed by
+    /// `transform`, but its stroke width (and tolerance) must ignore
+    /// `transform`'s scale, so `expand`/`expand_undashed` stroke the
+    /// already-transformed centerline instead of transforming the outline
+    /// afterward.
+    pub non_scaling_stroke: bool,
     // can add color etc, but it's irrelevant for stroke expansion
 }
 
+impl StyledPath {
+    /// A single scalar standing in for `transform`'s scale, for call sites
+    /// that only care about an overall magnitude (e.g. picking a flattening
+    /// tolerance, or the dilation distance in `svg_main`). Exact when
+    /// `transform` is a uniform scale (with any rotation/translation);
+    /// otherwise the geometric mean of the (possibly anisotropic) x/y
+    /// scale factors.
+    pub fn scale(&self) -> f64 {
+        scalar_scale(self.transform)
+    }
+}
+
+/// The geometric-mean scale factor of `affine`'s linear part: exact for a
+/// uniform scale (optionally composed with rotation/translation), and a
+/// reasonable single-number stand-in otherwise.
+fn scalar_scale(affine: Affine) -> f64 {
+    let [a, b, c, d, _, _] = affine.as_coeffs();
+    (a * d - b * c).abs().sqrt()
+}
+
+/// True if `affine`'s linear part is a uniform scale (with any
+/// rotation/reflection/translation): the images of the x and y basis
+/// vectors are perpendicular and the same length. Stroke expansion commutes
+/// exactly with such transforms (offset curves stay offset curves, circular
+/// arcs stay circular arcs), so `expand` can take the cheaper "scale the
+/// tolerance, expand, then transform" path; anything else (shear, anisotropic
+/// scale) needs the more expensive "expand at full tolerance, then
+/// transform" path to get the correct elliptical-pen result.
+fn is_uniform_scale(affine: Affine) -> bool {
+    let [a, b, c, d, _, _] = affine.as_coeffs();
+    const EPS: f64 = 1e-9;
+    (a * c + b * d).abs() < EPS && ((a * a + b * b) - (c * c + d * d)).abs() < EPS
+}
+
 pub struct SvgScene {
     pub paths: Vec<StyledPath>,
-    // maybe viewport and extra stuff
+    /// The document's `viewBox` (or, lacking one, its `width`/`height`
+    /// attributes), defaulting to 1024x1024 if neither is present. Used as
+    /// the drawing's bounds for output serialization and guard-band clipping.
+    pub viewport: Rect,
+}
+
+/// Parse an SVG `viewBox` attribute ("min-x min-y width height") into the
+/// `Rect` it describes.
+fn parse_viewbox(s: &str) -> Option<Rect> {
+    let mut lexer = Lexer(s);
+    let mut nums = [0.0; 4];
+    for n in &mut nums {
+        match lexer.next() {
+            Some(Token::Number(x)) => *n = x,
+            _ => return None,
+        }
+    }
+    let [x, y, w, h] = nums;
+    Some(Rect::new(x, y, x + w, y + h))
 }
 
 impl SvgScene {
     pub fn load(xml_string: &str) -> Result<SvgScene, Box<dyn Error>> {
         let doc = Document::parse(xml_string)?;
         let root = doc.root_element();
-        let mut scene = SvgScene { paths: vec![] };
+        let viewport = root
+            .attribute("viewBox")
+            .and_then(parse_viewbox)
+            .or_else(|| {
+                let width = root.attribute("width")?.parse().ok()?;
+                let height = root.attribute("height")?.parse().ok()?;
+                Some(Rect::new(0.0, 0.0, width, height))
+            })
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, 1024.0, 1024.0));
+        let mut scene = SvgScene {
+            paths: vec![],
+            viewport,
+        };
         for node in root.children() {
-            parse_rec(node, &mut scene, 1.0)?;
+            parse_rec(node, &mut scene, Affine::IDENTITY)?;
         }
         Ok(scene)
     }
+
+    /// Clip every path's already-expanded outline against `viewport`,
+    /// enlarged on each side by `guard_band_widths` multiples of that path's
+    /// own stroke half-width, so joins/caps straddling the edge stay intact.
+    /// Always returns line output; see `LoweredPath::clip`.
+    pub fn clip<L: Lowering>(
+        &self,
+        paths: &[LoweredPath<L>],
+        tolerance: f64,
+        guard_band_widths: f64,
+    ) -> Vec<LoweredPath<Line>> {
+        self.paths
+            .iter()
+            .zip(paths)
+            .map(|(styled, lowered)| {
+                let guard = guard_band_widths * 0.5 * styled.style.width;
+                let rect = self.viewport.inflate(guard, guard);
+                lowered.clip(rect, tolerance)
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -138,61 +229,118 @@ impl<'a> Lexer<'a> {
     }
 }
 
-// This is very fragile and is designed only to work on the test samples.
-fn get_transform_scale(transform: &str) -> f64 {
-    let mut scale = 1.0;
-    let mut lexer = Lexer(transform);
-    while let Some(func) = lexer.next() {
-        match func {
-            Token::Ident("scale") => {
-                if lexer.next() == Some(Token::Symbol('(')) {
-                    if let Some(Token::Number(n)) = lexer.next() {
-                        scale *= n;
-                        if lexer.next() != Some(Token::Symbol(')')) {
-                            break;
-                        }
-                    }
-                }
-            }
-            Token::Ident("matrix") => {
-                if lexer.next() == Some(Token::Symbol('(')) {
-                    if let Some(Token::Number(n)) = lexer.next() {
-                        scale *= n;
-                        while let Some(tok) = lexer.next() {
-                            if tok == Token::Symbol(')') {
-                                break;
-                            }
-                        }
-                    }
-                }
+/// Parse the arguments of a single transform function, up to and including
+/// its closing `)`, collecting the numbers and skipping commas/whitespace.
+fn parse_transform_args(lexer: &mut Lexer<'_>) -> Vec<f64> {
+    let mut args = vec![];
+    if lexer.next() != Some(Token::Symbol('(')) {
+        return args;
+    }
+    loop {
+        match lexer.next() {
+            Some(Token::Number(n)) => args.push(n),
+            Some(Token::Symbol(')')) | None => break,
+            Some(_) => (), // comma or whitespace-as-symbol
+        }
+    }
+    args
+}
+
+/// Parse an SVG `transform` attribute into the single `Affine` it's
+/// equivalent to: the matrix product, left to right, of each listed
+/// function (`translate`, `scale`, `rotate`, `skewX`, `skewY`, `matrix`),
+/// per the SVG spec. Unrecognized functions and malformed argument lists
+/// are treated as identity rather than aborting the whole attribute.
+fn parse_transform(s: &str) -> Affine {
+    let mut affine = Affine::IDENTITY;
+    let mut lexer = Lexer(s);
+    while let Some(tok) = lexer.next() {
+        let Token::Ident(name) = tok else {
+            continue;
+        };
+        let args = parse_transform_args(&mut lexer);
+        let local = match (name, &args[..]) {
+            ("translate", &[tx]) => Affine::translate((tx, 0.0)),
+            ("translate", &[tx, ty]) => Affine::translate((tx, ty)),
+            ("scale", &[sc]) => Affine::scale(sc),
+            ("scale", &[sx, sy]) => Affine::scale_non_uniform(sx, sy),
+            ("rotate", &[deg]) => Affine::rotate(deg.to_radians()),
+            ("rotate", &[deg, cx, cy]) => {
+                Affine::translate((cx, cy))
+                    * Affine::rotate(deg.to_radians())
+                    * Affine::translate((-cx, -cy))
             }
-            // Sloppy but hopefully good enough
-            _ => (),
+            ("skewX", &[deg]) => Affine::new([1.0, 0.0, deg.to_radians().tan(), 1.0, 0.0, 0.0]),
+            ("skewY", &[deg]) => Affine::new([1.0, deg.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+            ("matrix", &[a, b, c, d, e, f]) => Affine::new([a, b, c, d, e, f]),
+            _ => Affine::IDENTITY,
+        };
+        affine = affine * local;
+    }
+    affine
+}
+
+/// Parse an SVG `stroke-dasharray` value into a list of lengths, duplicating
+/// an odd-length list per the SVG spec so it always cycles evenly between
+/// "on" and "off".
+fn parse_dasharray(s: &str) -> Vec<f64> {
+    let mut lengths = vec![];
+    let mut lexer = Lexer(s);
+    while let Some(tok) = lexer.next() {
+        if let Token::Number(n) = tok {
+            lengths.push(n);
         }
     }
-    scale
+    if lengths.len() % 2 == 1 {
+        let doubled = lengths.clone();
+        lengths.extend(doubled);
+    }
+    lengths
+}
+
+/// Parse a CSS `style="..."` attribute into `(property, value)` declarations,
+/// splitting on `;` and then on the first `:` in each declaration. Malformed
+/// declarations (no `:`, or an empty property) are skipped rather than
+/// aborting the rest of the attribute.
+fn parse_style(s: &str) -> Vec<(&str, &str)> {
+    s.split(';')
+        .filter_map(|decl| {
+            let (prop, value) = decl.split_once(':')?;
+            let prop = prop.trim();
+            (!prop.is_empty()).then_some((prop, value.trim()))
+        })
+        .collect()
 }
 
-fn parse_rec(node: Node, scene: &mut SvgScene, scale: f64) -> Result<(), Box<dyn Error>> {
+fn parse_rec(node: Node, scene: &mut SvgScene, transform: Affine) -> Result<(), Box<dyn Error>> {
     match node.tag_name().name() {
         "g" => {
-            let mut child_scale = scale;
-            if let Some(transform) = node.attribute("transform") {
-                child_scale *= get_transform_scale(transform);
+            let mut child_transform = transform;
+            if let Some(attr) = node.attribute("transform") {
+                child_transform = child_transform * parse_transform(attr);
             }
             for child in node.children() {
-                parse_rec(child, scene, child_scale)?;
+                parse_rec(child, scene, child_transform)?;
             }
         }
         "path" => {
             let d = node.attribute("d").ok_or("missing 'd'")?;
             let path = BezPath::from_svg(d)?;
-            let width = node
-                .attribute("stroke-width")
+            // Per the CSS cascade, a declaration in `style=` overrides the
+            // same property given as a presentation attribute.
+            let style_decls = node.attribute("style").map(parse_style).unwrap_or_default();
+            let prop = |name: &str| -> Option<&str> {
+                style_decls
+                    .iter()
+                    .find(|(p, _)| *p == name)
+                    .map(|(_, v)| *v)
+                    .or_else(|| node.attribute(name))
+            };
+            let width = prop("stroke-width")
                 .map(|a| f64::from_str(a).unwrap_or(1.0))
                 .unwrap_or(4.0);
             let mut cap = Cap::Butt;
-            if let Some(linecap) = node.attribute("stroke-linecap") {
+            if let Some(linecap) = prop("stroke-linecap") {
                 match linecap {
                     "round" => cap = Cap::Round,
                     "square" => cap = Cap::Square,
@@ -200,27 +348,111 @@ fn parse_rec(node: Node, scene: &mut SvgScene, scale: f64) -> Result<(), Box<dyn
                 }
             }
             let mut join = Join::Miter;
-            if let Some(linejoin) = node.attribute("stroke-linejoin") {
+            if let Some(linejoin) = prop("stroke-linejoin") {
                 match linejoin {
                     "round" => join = Join::Round,
                     "bevel" => join = Join::Bevel,
                     _ => (),
                 }
             }
-            // TODO: miter limit
-            let style = Stroke::new(width).with_caps(cap).with_join(join);
-            scene.paths.push(StyledPath { path, style, scale });
+            let mut style = Stroke::new(width).with_caps(cap).with_join(join);
+            if let Some(miter_limit) = prop("stroke-miterlimit").and_then(|a| f64::from_str(a).ok())
+            {
+                style = style.with_miter_limit(miter_limit);
+            }
+            if let Some(dasharray) = prop("stroke-dasharray") {
+                if dasharray != "none" {
+                    let pattern = parse_dasharray(dasharray);
+                    // Per the SVG spec, a negative length anywhere in the
+                    // list makes the whole attribute invalid, so it's
+                    // ignored entirely rather than fed to `stroke_dashed`
+                    // (which would otherwise spin trying to advance through
+                    // a phase that never reaches a positive dash boundary).
+                    if !pattern.is_empty() && pattern.iter().all(|&n| n >= 0.0) {
+                        let dash_offset = prop("stroke-dashoffset")
+                            .and_then(|a| f64::from_str(a).ok())
+                            .unwrap_or(0.0);
+                        style = style.with_dashes(dash_offset, pattern);
+                    }
+                }
+            }
+            let non_scaling_stroke = prop("vector-effect") == Some("non-scaling-stroke");
+            scene.paths.push(StyledPath {
+                path,
+                style,
+                transform,
+                non_scaling_stroke,
+            });
         }
         _ => (),
     }
     Ok(())
 }
 
+/// Apply `affine` to every point of `path`. Used for `vector-effect:
+/// non-scaling-stroke` paths, which must be stroked *after* being placed by
+/// their transform rather than before, so the stroke width isn't scaled by it.
+fn transform_path(path: &BezPath, affine: Affine) -> BezPath {
+    let mut out = BezPath::new();
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => out.move_to(affine * p),
+            PathEl::LineTo(p) => out.line_to(affine * p),
+            PathEl::QuadTo(p1, p2) => out.quad_to(affine * p1, affine * p2),
+            PathEl::CurveTo(p1, p2, p3) => out.curve_to(affine * p1, affine * p2, affine * p3),
+            PathEl::ClosePath => out.close_path(),
+        }
+    }
+    out
+}
+
 impl SvgScene {
+    /// Stroke width is specified in each path's local user units, so the
+    /// correct order is to expand the *untransformed*
+    /// centerline there and only then transform the resulting outline by
+    /// `path.transform` — for a non-uniform `transform` that's the only way
+    /// to get the correct anisotropic ("elliptical pen") result, since
+    /// scaling the input tolerance can't express that. When `transform` is a
+    /// uniform scale (with any rotation/translation), expansion commutes
+    /// with it exactly, so `tolerance` is scaled down first to flatten at
+    /// the right output-space density instead of over- or under-tessellating.
     pub fn expand<L: Lowering>(&self, tolerance: f64) -> Vec<LoweredPath<L>> {
         self.paths
             .iter()
-            .map(|path| stroke_undashed(&path.path, &path.style, tolerance / path.scale))
+            .map(|path| {
+                if path.non_scaling_stroke {
+                    let placed = transform_path(&path.path, path.transform);
+                    stroke_dashed(&placed, &path.style, tolerance)
+                } else {
+                    let local_tol = if is_uniform_scale(path.transform) {
+                        tolerance / path.scale()
+                    } else {
+                        tolerance
+                    };
+                    stroke_dashed(&path.path, &path.style, local_tol).transform(path.transform)
+                }
+            })
+            .collect()
+    }
+
+    /// Like `expand`, but ignoring each path's dash pattern, for measuring
+    /// the extra segment cost dashing adds at a given tolerance.
+    pub fn expand_undashed<L: Lowering>(&self, tolerance: f64) -> Vec<LoweredPath<L>> {
+        self.paths
+            .iter()
+            .map(|path| {
+                if path.non_scaling_stroke {
+                    let placed = transform_path(&path.path, path.transform);
+                    stroke_undashed(&placed, &path.style, tolerance)
+                } else {
+                    let local_tol = if is_uniform_scale(path.transform) {
+                        tolerance / path.scale()
+                    } else {
+                        tolerance
+                    };
+                    stroke_undashed(&path.path, &path.style, local_tol).transform(path.transform)
+                }
+            })
             .collect()
     }
 
@@ -229,10 +461,9 @@ impl SvgScene {
         out: &mut impl Write,
         paths: &[LoweredPath<impl Lowering>],
     ) -> Result<(), Box<dyn Error>> {
-        // these should probably be fields in the scene
-        let width = 1024;
-        let height = 1024;
-        writeln!(out, "<svg width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" xmlns=\"http://www.w3.org/2000/svg\">")?;
+        let Rect { x0, y0, x1, y1 } = self.viewport;
+        let (width, height) = (x1 - x0, y1 - y0);
+        writeln!(out, "<svg width=\"{width}\" height=\"{height}\" viewBox=\"{x0} {y0} {width} {height}\" xmlns=\"http://www.w3.org/2000/svg\">")?;
         for path in paths {
             let svg_path = path.to_svg();
             writeln!(out, "  <path d='{svg_path}' />")?;
@@ -253,6 +484,19 @@ pub struct SvgArgs {
     primitive: Option<String>,
     #[arg(short, long)]
     output_file: Option<String>,
+    /// Dilate (or, if negative, erode) each path by this distance before
+    /// stroke expansion.
+    #[arg(long)]
+    dilate: Option<f64>,
+    /// Clip expanded output to the viewport (enlarged by `guard_band`
+    /// stroke half-widths) before writing `--output-file`, instead of
+    /// emitting geometry that will never be visible.
+    #[arg(long)]
+    clip: bool,
+    /// Guard-band width, in multiples of each path's stroke half-width, used
+    /// by `--clip` so joins/caps straddling the viewport edge aren't cut off.
+    #[arg(long, default_value = "4.0")]
+    guard_band: f64,
 }
 
 #[derive(Clone, Parser)]
@@ -269,8 +513,14 @@ impl Default for PrimType {
 
 pub fn svg_main(args: SvgArgs) {
     let xml_str = std::fs::read_to_string(&args.filename).unwrap();
-    let scene = SvgScene::load(&xml_str).unwrap();
+    let mut scene = SvgScene::load(&xml_str).unwrap();
     let tolerance = args.tolerance.unwrap_or(1.0);
+    if let Some(d) = args.dilate {
+        for path in &mut scene.paths {
+            let scale = path.scale();
+            path.path = crate::dilate::dilate(&path.path, d / scale, tolerance / scale);
+        }
+    }
     let prim_type = match args.primitive.as_deref() {
         Some("l") => PrimType::Line,
         Some("a") => PrimType::Arc,
@@ -296,11 +546,21 @@ pub fn svg_main(args: SvgArgs) {
         match prim_type {
             PrimType::Line => {
                 let paths: Vec<LoweredPath<Line>> = scene.expand(tolerance);
-                scene.to_svg(&mut f, &paths).unwrap();
+                if args.clip {
+                    let paths = scene.clip(&paths, tolerance, args.guard_band);
+                    scene.to_svg(&mut f, &paths).unwrap();
+                } else {
+                    scene.to_svg(&mut f, &paths).unwrap();
+                }
             }
             PrimType::Arc => {
                 let paths: Vec<LoweredPath<ArcSegment>> = scene.expand(tolerance);
-                scene.to_svg(&mut f, &paths).unwrap();
+                if args.clip {
+                    let paths = scene.clip(&paths, tolerance, args.guard_band);
+                    scene.to_svg(&mut f, &paths).unwrap();
+                } else {
+                    scene.to_svg(&mut f, &paths).unwrap();
+                }
             }
         }
     }
@@ -311,3 +571,30 @@ pub fn svg_main(args: SvgArgs) {
         println!("{} path segments", p.segments().count());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dasharray_even_length_is_unchanged() {
+        assert_eq!(parse_dasharray("4 2 1 3"), vec![4.0, 2.0, 1.0, 3.0]);
+    }
+
+    /// Per the SVG spec, an odd-length dasharray is duplicated so it always
+    /// cycles evenly between "on" and "off".
+    #[test]
+    fn dasharray_odd_length_is_doubled() {
+        assert_eq!(parse_dasharray("4 2 1"), vec![4.0, 2.0, 1.0, 4.0, 2.0, 1.0]);
+    }
+
+    /// A negative entry is structurally fine for `parse_dasharray` itself
+    /// (it's just a number lexer); rejecting the whole attribute when one is
+    /// negative is `parse_rec`'s job, applied to this function's output.
+    #[test]
+    fn dasharray_negative_entry_parses_but_is_rejected_downstream() {
+        let pattern = parse_dasharray("4 -2 1 3");
+        assert_eq!(pattern, vec![4.0, -2.0, 1.0, 3.0]);
+        assert!(!pattern.iter().all(|&n| n >= 0.0));
+    }
+}
@@ -52,16 +52,21 @@ pub fn to_rvg_main(args: ToRvgArgs) {
     println!("rvg.viewport = viewport(0, 0, {}, {})", width, height);
     println!("rvg.scene = scene({{");
     for path in &scene.paths {
-        let scale = path.scale;
         let d = to_rvg_path(path.path.elements());
-        let t = if scale == 1.0 {
+        let [a, b, c, d_coef, e, f] = path.transform.as_coeffs();
+        let t = if path.transform == kurbo::Affine::IDENTITY {
             String::new()
         } else {
-            format!(":affine({}, 0, 0, 0, {}, 0)", scale, scale)
+            // RVG's affine is row-major (m00, m01, tx, m10, m11, ty); kurbo's
+            // is column-major (a, b, c, d, e, f) with x' = a*x + c*y + e.
+            format!(":affine({a}, {c}, {e}, {b}, {d_coef}, {f})")
         };
+        // Stroke width is in the path's pre-transform local units; since the
+        // outline is now emitted pre-transform too (the `:affine` above does
+        // the transforming), no extra scale factor belongs here.
         println!(
             "  fill(path{{{d}}}{t}:stroked({}), rgba8(0, 0, 0, 255)),",
-            path.style.width * scale
+            path.style.width
         );
     }
     println!("}})");